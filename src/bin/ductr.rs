@@ -0,0 +1,270 @@
+//! `ductr` command-line tool, exposing the crate's manipulation functions to shell scripts
+//!
+//! Build it with `cargo install ductr --features cli`, then run `ductr help` for the list of commands.
+//! Every input or output path may be `-` to read from stdin or write to stdout.
+
+use std::{collections::HashMap, fs, io::{self, Read, Write}, process::ExitCode};
+use ductr::{AnsiMode, AnymapImage, DitherMethod, GreyscaleMethod};
+use ductr::compare::{self, DiffOptions, DiffStyle, Metric};
+
+const USAGE: &str = "\
+usage: ductr <command> [arguments] [options]
+
+commands:
+  info <input>                       print the format, encoding, dimensions and saturation
+  convert <input> <output>           rewrite an image, optionally in another format
+      --to pbm|pgm|ppm
+  invert <input> <output>            invert every pixel
+  greyscale <input> <output>         greyscale a PPM image, keeping the PPM format unless --pgm is given
+      --method average|rec601|rec709|linear|lightness|desaturate|red|green|blue
+      --pgm
+  resize <input> <output>            resize to --width and/or --height, keeping the aspect ratio when one is missing
+      --width W --height H
+  crop <input> <output>              crop the rectangle starting at (--top, --left)
+      --top T --left L --width W --height H
+  compare <a> <b>                    print every comparison metric between two images
+  diff <a> <b> <output>              write an image of the differences, exits with 1 when the images differ
+      --tolerance N --overlay
+  preview <input>                    draw the image in the terminal
+      --columns N (default: $COLUMNS or 80)
+      --mode truecolor|256|ascii|sixel|kitty|iterm
+
+options shared by every command writing an image:
+  --ascii                            write the plain (ascii) format instead of the raw (binary) one
+
+paths may be `-` to read from stdin or write to stdout
+";
+
+// options taking a value, every other option is a flag
+const VALUED: &[&str] = &["to", "method", "width", "height", "top", "left", "tolerance", "columns", "mode"];
+const FLAGS: &[&str] = &["ascii", "pgm", "overlay"];
+
+// Parsed command line, split into positional arguments, valued options and flags
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args { positional: Vec::new(), options: HashMap::new(), flags: Vec::new() };
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if VALUED.contains(&name) => {
+                    let value = args.next().ok_or(format!("Error: option --{} needs a value", name))?;
+                    parsed.options.insert(name.to_string(), value);
+                },
+                Some(name) if FLAGS.contains(&name) => parsed.flags.push(name.to_string()),
+                Some(name) => return Err(format!("Error: unknown option --{}", name)),
+                None => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    // Helper function returning the positional arguments, checking their amount
+    fn paths(&self, expected: usize) -> Result<&[String], String> {
+        if self.positional.len() != expected { return Err(format!("Error: expected {} path(s), got {}", expected, self.positional.len())) }
+        Ok(&self.positional)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn number(&self, name: &str) -> Result<Option<usize>, String> {
+        self.options.get(name)
+                    .map(|value| value.parse::<usize>().map_err(|_| format!("Error: --{} expects a positive number ({})", name, value)))
+                    .transpose()
+    }
+
+    fn required(&self, name: &str) -> Result<usize, String> {
+        self.number(name)?.ok_or(format!("Error: option --{} is required", name))
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let result = Args::parse(args).and_then(|args| run(&command, &args));
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("ductr: {}", e.trim_start_matches("Error: "));
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Helper function running a single command, returning the exit code on success
+fn run(command: &str, args: &Args) -> Result<ExitCode, String> {
+    match command {
+        "info" => {
+            let [input] = args.paths(1)? else { unreachable!() };
+            let bytes = read_bytes(input)?;
+            let image = AnymapImage::read_from_bytes(&bytes)?;
+            let (height, width) = image.dimensions();
+            let encoding = if matches!(bytes[1], b'1'..=b'3') { "ascii" } else { "binary" };
+            let format = match bytes[1] { b'1' | b'4' => "pbm", b'2' | b'5' => "pgm", _ => "ppm" };
+            println!("format: {} ({})", format, encoding);
+            println!("dimensions: {}x{}", width, height);
+            if let (false, Some(saturation)) = (format == "pbm", header_saturation(&bytes)) {
+                println!("saturation: {}", saturation);
+            }
+        },
+        "convert" => {
+            let [input, output] = args.paths(2)? else { unreachable!() };
+            let image = read_image(input)?;
+            let image = match args.options.get("to") {
+                Some(format) => image.to_format(format)?,
+                None => image,
+            };
+            write_image(&image, output, args)?;
+        },
+        "invert" => {
+            let [input, output] = args.paths(2)? else { unreachable!() };
+            let mut image = read_image(input)?;
+            image.invert();
+            write_image(&image, output, args)?;
+        },
+        "greyscale" => {
+            let [input, output] = args.paths(2)? else { unreachable!() };
+            let mut image = read_image(input)?;
+            let method = greyscale_method(args.options.get("method").map(String::as_str).unwrap_or("average"))?;
+            if args.flag("pgm") {
+                image = image.to_pgm(method)?;
+            } else {
+                image.greyscale_with(method)?;
+            }
+            write_image(&image, output, args)?;
+        },
+        "resize" => {
+            let [input, output] = args.paths(2)? else { unreachable!() };
+            let image = read_image(input)?;
+            let (height, width) = image.dimensions();
+            let (new_height, new_width) = match (args.number("height")?, args.number("width")?) {
+                (Some(h), Some(w)) => (h, w),
+                (Some(h), None) => (h, keep_aspect(width, h, height)?),
+                (None, Some(w)) => (keep_aspect(height, w, width)?, w),
+                (None, None) => return Err("Error: resize needs --width and/or --height".to_string()),
+            };
+            write_image(&image.resize(new_height, new_width)?, output, args)?;
+        },
+        "crop" => {
+            let [input, output] = args.paths(2)? else { unreachable!() };
+            let image = read_image(input)?;
+            let cropped = image.crop(args.required("top")?, args.required("left")?, args.required("height")?, args.required("width")?)?;
+            write_image(&cropped, output, args)?;
+        },
+        "compare" => {
+            let [a, b] = args.paths(2)? else { unreachable!() };
+            if a == "-" && b == "-" { return Err("Error: only one image can be read from stdin".to_string()) }
+            let results = compare::summary(&read_image(a)?, &read_image(b)?)?;
+            let metrics: [(&str, &Metric); 7] = [("mse", &results.mse), ("rmse", &results.rmse), ("psnr", &results.psnr),
+                                                 ("mae", &results.mean_absolute_error), ("max", &results.max_error),
+                                                 ("ssim", &results.ssim), ("ms-ssim", &results.ms_ssim)];
+            for (name, metric) in metrics {
+                let channels = metric.channels.iter().map(|v| format!("{:.4}", v)).collect::<Vec<String>>().join(" ");
+                println!("{:<8} {:.4} ({})", name, metric.combined, channels);
+            }
+        },
+        "diff" => {
+            let [a, b, output] = args.paths(3)? else { unreachable!() };
+            if a == "-" && b == "-" { return Err("Error: only one image can be read from stdin".to_string()) }
+            let style = if args.flag("overlay") { DiffStyle::Overlay } else { DiffStyle::Heatmap };
+            let options = DiffOptions { style, tolerance: args.number("tolerance")?.unwrap_or(0) };
+            let difference = compare::diff_image(&read_image(a)?, &read_image(b)?, &options)?;
+            write_image(&difference.image, output, args)?;
+            eprintln!("differing pixels: {}", difference.differing_pixels);
+            if let Some(((top, left), (bottom, right))) = difference.bounding_box {
+                eprintln!("bounding box: ({}, {}) to ({}, {})", top, left, bottom, right);
+                return Ok(ExitCode::from(1))
+            }
+        },
+        "preview" => {
+            let [input] = args.paths(1)? else { unreachable!() };
+            let image = read_image(input)?;
+            let columns = match args.number("columns")? {
+                Some(columns) => columns,
+                None => std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(80),
+            };
+            let mut stdout = io::stdout().lock();
+            match args.options.get("mode").map(String::as_str).unwrap_or("truecolor") {
+                "truecolor" => write_text(&mut stdout, &image.to_ansi_with(columns, AnsiMode::TrueColor)?)?,
+                "256" => write_text(&mut stdout, &image.to_ansi_with(columns, AnsiMode::Color256)?)?,
+                "ascii" => write_text(&mut stdout, &image.to_ansi_with(columns, AnsiMode::Ascii)?)?,
+                "sixel" => image.write_sixel(&mut stdout, 256, DitherMethod::FloydSteinberg)?,
+                "kitty" => image.write_kitty(&mut stdout)?,
+                "iterm" => image.write_iterm(&mut stdout)?,
+                mode => return Err(format!("Error: unknown preview mode {:?}", mode)),
+            }
+        },
+        "help" | "--help" | "-h" => print!("{}", USAGE),
+        "" => return Err(format!("Error: missing command\n\n{}", USAGE)),
+        command => return Err(format!("Error: unknown command {:?}\n\n{}", command, USAGE)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+// Helper function reading every byte of a file, or of stdin for `-`
+fn read_bytes(path: &str) -> Result<Vec<u8>, String> {
+    if path != "-" { return fs::read(path).map_err(|e| format!("Error: could not read {}: {}", path, e)) }
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes).map_err(|e| format!("Error: could not read stdin: {}", e))?;
+    Ok(bytes)
+}
+
+fn read_image(path: &str) -> Result<AnymapImage, String> {
+    AnymapImage::read_from_bytes(&read_bytes(path)?)
+}
+
+// Helper function writing an image to a file, or to stdout for `-`, in the encoding picked by `--ascii`
+fn write_image(image: &AnymapImage, path: &str, args: &Args) -> Result<(), String> {
+    let mut writer: Box<dyn Write> = match path {
+        "-" => Box::new(io::stdout().lock()),
+        _ => Box::new(fs::File::create(path).map_err(|e| format!("Error: could not create {}: {}", path, e))?),
+    };
+    match args.flag("ascii") {
+        true => image.write_ascii_to(&mut writer)?,
+        false => image.write_binary_to(&mut writer)?,
+    }
+    writer.flush().map_err(|e| format!("Error: could not write {}: {}", path, e))
+}
+
+fn write_text<W: Write>(writer: &mut W, text: &str) -> Result<(), String> {
+    writer.write_all(text.as_bytes()).map_err(|e| format!("Error: could not write stdout: {}", e))
+}
+
+// Helper function returning the saturation written in a PGM or PPM header, skipping comments
+fn header_saturation(bytes: &[u8]) -> Option<usize> {
+    let header = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).into_owned();
+    header.lines()
+          .map(|line| line.split('#').next().unwrap_or(""))
+          .flat_map(str::split_ascii_whitespace)
+          .nth(3)
+          .and_then(|value| value.parse().ok())
+}
+
+// Helper function scaling `size` by `new / old` to the nearest whole pixel, rejecting sizes that overflow
+fn keep_aspect(size: usize, new: usize, old: usize) -> Result<usize, String> {
+    size.checked_mul(new)
+        .and_then(|scaled| scaled.checked_add(old / 2))
+        .map(|scaled| (scaled / old.max(1)).max(1))
+        .ok_or(format!("Error: resized dimensions are too large ({} * {} / {})", size, new, old))
+}
+
+fn greyscale_method(name: &str) -> Result<GreyscaleMethod, String> {
+    Ok(match name {
+        "average" => GreyscaleMethod::Average,
+        "rec601" => GreyscaleMethod::Rec601,
+        "rec709" => GreyscaleMethod::Rec709,
+        "linear" => GreyscaleMethod::LinearLuminance,
+        "lightness" => GreyscaleMethod::Lightness,
+        "desaturate" => GreyscaleMethod::Desaturate,
+        "red" => GreyscaleMethod::Channel(0),
+        "green" => GreyscaleMethod::Channel(1),
+        "blue" => GreyscaleMethod::Channel(2),
+        _ => return Err(format!("Error: unknown greyscale method {:?}", name)),
+    })
+}
@@ -0,0 +1,8 @@
+//! `pamscale` drop-in replacement, see `ductr::netpbm::pamscale`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("pamscale", netpbm::pamscale)
+}
@@ -0,0 +1,8 @@
+//! `pgmtopbm` drop-in replacement, see `ductr::netpbm::pgmtopbm`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("pgmtopbm", netpbm::pgmtopbm)
+}
@@ -0,0 +1,8 @@
+//! `pnmcat` drop-in replacement, see `ductr::netpbm::pnmcat`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("pnmcat", netpbm::pnmcat)
+}
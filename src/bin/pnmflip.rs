@@ -0,0 +1,8 @@
+//! `pnmflip` drop-in replacement, see `ductr::netpbm::pnmflip`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("pnmflip", netpbm::pnmflip)
+}
@@ -0,0 +1,8 @@
+//! `pnminvert` drop-in replacement, see `ductr::netpbm::pnminvert`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("pnminvert", netpbm::pnminvert)
+}
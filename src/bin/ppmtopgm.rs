@@ -0,0 +1,8 @@
+//! `ppmtopgm` drop-in replacement, see `ductr::netpbm::ppmtopgm`
+
+use std::process::ExitCode;
+use ductr::netpbm;
+
+fn main() -> ExitCode {
+    netpbm::run("ppmtopgm", netpbm::ppmtopgm)
+}
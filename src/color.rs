@@ -0,0 +1,321 @@
+//! Color space conversions for single pixels and whole PPM images
+//!
+//! Every conversion goes through gamma encoded (sRGB) RGB, the space PPM buffers are stored in.
+//! Pixels are passed as `[f64; 3]` using the following ranges:
+//! - RGB & linear RGB: each channel in `0.0..=1.0`
+//! - HSV & HSL: hue in degrees `0.0..360.0`, saturation and value/lightness in `0.0..=1.0`
+//! - YCbCr: luma in `0.0..=1.0`, chroma in `-0.5..=0.5` (full range, no footroom or headroom)
+//! - CIE XYZ: D65 white point with `Y = 1.0` for white
+//! - CIE Lab: lightness in `0.0..=100.0`, a* and b* roughly in `-128.0..=127.0`
+
+use crate::AnymapImage;
+
+/// Luma coefficients used by YCbCr conversions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YCbCrStandard {
+    /// ITU-R BT.601 (standard definition video, JPEG)
+    Bt601,
+    /// ITU-R BT.709 (high definition video)
+    Bt709,
+}
+
+impl YCbCrStandard {
+    // Helper function returning the (red, blue) luma coefficients of the standard
+    fn coefficients(&self) -> (f64, f64) {
+        match self {
+            YCbCrStandard::Bt601 => (0.299, 0.114),
+            YCbCrStandard::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// Color spaces supported by `convert` and the whole image conversions of `AnymapImage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// gamma encoded sRGB
+    Rgb,
+    /// linear light sRGB primaries
+    LinearRgb,
+    /// hue, saturation, value
+    Hsv,
+    /// hue, saturation, lightness
+    Hsl,
+    /// luma and blue/red difference chroma
+    YCbCr(YCbCrStandard),
+    /// CIE 1931 XYZ (D65)
+    Xyz,
+    /// CIE L*a*b* (D65)
+    Lab,
+}
+
+// D65 reference white in XYZ
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+/// Converts a single pixel between two color spaces
+///
+/// # Example:
+/// ```
+/// use ductr::color::{convert, ColorSpace};
+///
+/// let hsv = convert([1.0, 0.0, 0.0], ColorSpace::Rgb, ColorSpace::Hsv);
+/// assert_eq!(hsv, [0.0, 1.0, 1.0]);
+/// ```
+pub fn convert(pixel: [f64; 3], from: ColorSpace, to: ColorSpace) -> [f64; 3] {
+    if from == to { return pixel }
+    let rgb = match from {
+        ColorSpace::Rgb => pixel,
+        ColorSpace::LinearRgb => linear_to_rgb(pixel),
+        ColorSpace::Hsv => hsv_to_rgb(pixel),
+        ColorSpace::Hsl => hsl_to_rgb(pixel),
+        ColorSpace::YCbCr(standard) => ycbcr_to_rgb(pixel, standard),
+        ColorSpace::Xyz => xyz_to_rgb(pixel),
+        ColorSpace::Lab => lab_to_rgb(pixel),
+    };
+    match to {
+        ColorSpace::Rgb => rgb,
+        ColorSpace::LinearRgb => rgb_to_linear(rgb),
+        ColorSpace::Hsv => rgb_to_hsv(rgb),
+        ColorSpace::Hsl => rgb_to_hsl(rgb),
+        ColorSpace::YCbCr(standard) => rgb_to_ycbcr(rgb, standard),
+        ColorSpace::Xyz => rgb_to_xyz(rgb),
+        ColorSpace::Lab => rgb_to_lab(rgb),
+    }
+}
+
+/// Converts a gamma encoded sRGB pixel to HSV
+pub fn rgb_to_hsv(rgb: [f64; 3]) -> [f64; 3] {
+    let (max, min) = (rgb[0].max(rgb[1]).max(rgb[2]), rgb[0].min(rgb[1]).min(rgb[2]));
+    let chroma = max - min;
+    let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+    [hue(rgb, max, chroma), saturation, max]
+}
+
+/// Converts an HSV pixel to gamma encoded sRGB
+pub fn hsv_to_rgb(hsv: [f64; 3]) -> [f64; 3] {
+    let chroma = hsv[2] * hsv[1];
+    from_hue(hsv[0], chroma, hsv[2] - chroma)
+}
+
+/// Converts a gamma encoded sRGB pixel to HSL
+pub fn rgb_to_hsl(rgb: [f64; 3]) -> [f64; 3] {
+    let (max, min) = (rgb[0].max(rgb[1]).max(rgb[2]), rgb[0].min(rgb[1]).min(rgb[2]));
+    let chroma = max - min;
+    let lightness = (max + min) / 2.0;
+    let saturation = if lightness > 0.0 && lightness < 1.0 { chroma / (1.0 - (2.0 * lightness - 1.0).abs()) } else { 0.0 };
+    [hue(rgb, max, chroma), saturation, lightness]
+}
+
+/// Converts an HSL pixel to gamma encoded sRGB
+pub fn hsl_to_rgb(hsl: [f64; 3]) -> [f64; 3] {
+    let chroma = (1.0 - (2.0 * hsl[2] - 1.0).abs()) * hsl[1];
+    from_hue(hsl[0], chroma, hsl[2] - chroma / 2.0)
+}
+
+/// Converts a gamma encoded sRGB pixel to full range YCbCr
+pub fn rgb_to_ycbcr(rgb: [f64; 3], standard: YCbCrStandard) -> [f64; 3] {
+    let (kr, kb) = standard.coefficients();
+    let luma = kr * rgb[0] + (1.0 - kr - kb) * rgb[1] + kb * rgb[2];
+    [luma, (rgb[2] - luma) / (2.0 * (1.0 - kb)), (rgb[0] - luma) / (2.0 * (1.0 - kr))]
+}
+
+/// Converts a full range YCbCr pixel to gamma encoded sRGB
+pub fn ycbcr_to_rgb(ycbcr: [f64; 3], standard: YCbCrStandard) -> [f64; 3] {
+    let (kr, kb) = standard.coefficients();
+    let red = ycbcr[0] + 2.0 * (1.0 - kr) * ycbcr[2];
+    let blue = ycbcr[0] + 2.0 * (1.0 - kb) * ycbcr[1];
+    let green = (ycbcr[0] - kr * red - kb * blue) / (1.0 - kr - kb);
+    [red, green, blue]
+}
+
+/// Decodes a gamma encoded sRGB pixel to linear light
+pub fn rgb_to_linear(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(|c| if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) })
+}
+
+/// Encodes a linear light pixel with the sRGB transfer curve
+pub fn linear_to_rgb(linear: [f64; 3]) -> [f64; 3] {
+    linear.map(|c| if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 })
+}
+
+/// Converts a gamma encoded sRGB pixel to CIE XYZ
+pub fn rgb_to_xyz(rgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb_to_linear(rgb);
+    [0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+     0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+     0.0193339 * r + 0.1191920 * g + 0.9503041 * b]
+}
+
+/// Converts a CIE XYZ pixel to gamma encoded sRGB
+pub fn xyz_to_rgb(xyz: [f64; 3]) -> [f64; 3] {
+    let [x, y, z] = xyz;
+    linear_to_rgb([ 3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+                   -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+                    0.0556434 * x - 0.2040259 * y + 1.0572252 * z])
+}
+
+/// Converts a CIE XYZ pixel to CIE Lab
+pub fn xyz_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let f = |t: f64| if t > (6.0f64 / 29.0).powi(3) { t.cbrt() } else { t / (3.0 * (6.0f64 / 29.0).powi(2)) + 4.0 / 29.0 };
+    let [fx, fy, fz] = [f(xyz[0] / WHITE[0]), f(xyz[1] / WHITE[1]), f(xyz[2] / WHITE[2])];
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Converts a CIE Lab pixel to CIE XYZ
+pub fn lab_to_xyz(lab: [f64; 3]) -> [f64; 3] {
+    let f_inv = |t: f64| if t > 6.0 / 29.0 { t.powi(3) } else { 3.0 * (6.0f64 / 29.0).powi(2) * (t - 4.0 / 29.0) };
+    let fy = (lab[0] + 16.0) / 116.0;
+    [WHITE[0] * f_inv(fy + lab[1] / 500.0), WHITE[1] * f_inv(fy), WHITE[2] * f_inv(fy - lab[2] / 200.0)]
+}
+
+/// Converts a gamma encoded sRGB pixel to CIE Lab
+pub fn rgb_to_lab(rgb: [f64; 3]) -> [f64; 3] {
+    xyz_to_lab(rgb_to_xyz(rgb))
+}
+
+/// Converts a CIE Lab pixel to gamma encoded sRGB
+pub fn lab_to_rgb(lab: [f64; 3]) -> [f64; 3] {
+    xyz_to_rgb(lab_to_xyz(lab))
+}
+
+// Helper function computing the hue in degrees shared by HSV and HSL
+fn hue(rgb: [f64; 3], max: f64, chroma: f64) -> f64 {
+    if chroma == 0.0 { return 0.0 }
+    let sector = if max == rgb[0] {
+        ((rgb[1] - rgb[2]) / chroma).rem_euclid(6.0)
+    } else if max == rgb[1] {
+        (rgb[2] - rgb[0]) / chroma + 2.0
+    } else {
+        (rgb[0] - rgb[1]) / chroma + 4.0
+    };
+    60.0 * sector
+}
+
+// Helper function rebuilding RGB from a hue, chroma and the amount added to every channel, shared by HSV and HSL
+fn from_hue(hue: f64, chroma: f64, offset: f64) -> [f64; 3] {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r + offset, g + offset, b + offset]
+}
+
+impl AnymapImage {
+
+    /// Converts every pixel of the AnymapImage struct into the given color space
+    /// Returns one `[f64; 3]` per pixel, in the ranges described in the `color` module
+    ///
+    /// # Rules for arguments:
+    /// - PGM images are treated as grey RGB pixels
+    /// - PBM formats are not accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::color::ColorSpace;
+    ///
+    /// let ppm = AnymapImage::ppm(vec![0, 255, 0], 255, 1, 1).unwrap();
+    /// let hsv = ppm.to_color_space(ColorSpace::Hsv).unwrap();
+    /// assert_eq!(hsv, vec![[120.0, 1.0, 1.0]]);
+    /// ```
+    pub fn to_color_space(&self, space: ColorSpace) -> Result<Vec<[f64; 3]>, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be converted to other color spaces".to_string()) }
+        if self.saturation == 0 { return Err("Error: images with a saturation of 0 cannot be converted to other color spaces".to_string()) }
+
+        let max = self.saturation as f64;
+        let rgb_pixels: Vec<[f64; 3]> = match self.magic_number.as_str() {
+            "ppm" => self.buffer.chunks(3).map(|p| [p[0] as f64 / max, p[1] as f64 / max, p[2] as f64 / max]).collect(),
+            _ => self.buffer.iter().map(|&v| [v as f64 / max; 3]).collect(),
+        };
+        Ok(rgb_pixels.into_iter().map(|pixel| convert(pixel, ColorSpace::Rgb, space)).collect())
+    }
+
+    /// # Constructor for a PPM image from pixels in any color space
+    /// Converts every pixel back to gamma encoded RGB and quantizes it to `saturation`
+    ///
+    /// # Rules for arguments:
+    /// - length of `pixels` must equal the product of the width and height inputs
+    /// - `saturation` argument must fall in the range of `1..=255`
+    ///
+    /// # Notes
+    /// - colors outside of the RGB gamut are clamped
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::color::ColorSpace;
+    ///
+    /// let ppm = AnymapImage::from_color_space(&[[240.0, 1.0, 0.5]], ColorSpace::Hsl, 255, 1, 1).unwrap();
+    /// assert_eq!(ppm.get_buffer(), vec![0, 0, 255]);
+    /// ```
+    pub fn from_color_space(pixels: &[[f64; 3]], space: ColorSpace, saturation: usize, height: usize, width: usize) -> Result<Self, String> {
+        if pixels.len() != height*width { return Err(format!("Error: could not create PPM object: pixel vector does not fit given dimensions: (pixel count: {}) != (given dimensions [h*w]: {})", pixels.len(), height*width)) }
+        if saturation == 0 || saturation > 255 { return Err(format!("Error: could not create PPM object: saturation must fall in the range of 1..=255 ({})", saturation)) }
+
+        let max = saturation as f64;
+        let buffer = pixels.iter()
+                           .flat_map(|&pixel| convert(pixel, space, ColorSpace::Rgb))
+                           .map(|c| (c * max).round().clamp(0.0, max) as u8)
+                           .collect();
+        AnymapImage::ppm(buffer, saturation, height, width)
+    }
+
+    /// Rotates the hue of every pixel of the AnymapImage struct by the given amount of degrees
+    ///
+    /// # Rules for arguments:
+    /// - only PPM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // turn red into green
+    /// let mut ppm = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+    /// ppm.adjust_hue(120.0).unwrap();
+    /// assert_eq!(ppm.get_buffer(), vec![0, 255, 0]);
+    /// ```
+    pub fn adjust_hue(&mut self, degrees: f64) -> Result<(), String> {
+        if degrees.is_nan() { return Err("Error: hue rotation must be a number".to_string()) }
+        self.map_hsv(|[h, s, v]| [(h + degrees).rem_euclid(360.0), s, v])
+    }
+
+    /// Scales the saturation (colorfulness) of every pixel of the AnymapImage struct
+    ///
+    /// # Rules for arguments:
+    /// - `factor` must not be negative (0.0 removes all color, 1.0 leaves the image unchanged)
+    /// - only PPM formats are accepted
+    ///
+    /// # Notes
+    /// - saturation is measured in HSV and clamped to its maximum
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut ppm = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+    /// ppm.adjust_saturation(0.0).unwrap();
+    /// assert_eq!(ppm.get_buffer(), vec![255, 255, 255]);
+    /// ```
+    pub fn adjust_saturation(&mut self, factor: f64) -> Result<(), String> {
+        if factor.is_nan() || factor < 0.0 { return Err(format!("Error: saturation factor must not be negative ({})", factor)) }
+        self.map_hsv(|[h, s, v]| [h, (s * factor).min(1.0), v])
+    }
+
+    // Helper function applying an operation to the HSV representation of every pixel
+    fn map_hsv<F>(&mut self, op: F) -> Result<(), String>
+    where F: Fn([f64; 3]) -> [f64; 3] {
+        if self.magic_number != "ppm" { return Err("Error: only ppm images can have their hue or saturation adjusted".to_string()) }
+
+        let pixels = self.to_color_space(ColorSpace::Hsv)?
+                         .into_iter()
+                         .map(op)
+                         .collect::<Vec<[f64; 3]>>();
+        *self = AnymapImage::from_color_space(&pixels, ColorSpace::Hsv, self.saturation, self.height, self.width)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+//! Quantitative comparison between two images of the same format and dimensions
+//!
+//! Every metric is computed per color channel (1 for PBM & PGM, 3 for PPM) and combined over all channels.
+//! Errors are measured in pixel values, so a PGM with a saturation of 255 has errors in the range of `0..=255`.
+
+use crate::AnymapImage;
+use crate::filter::gaussian_blur_plane;
+
+/// Result of a comparison metric, per color channel and combined over every channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    /// value of the metric for each color channel
+    pub channels: Vec<f64>,
+    /// value of the metric over the whole image
+    pub combined: f64,
+}
+
+/// Every comparison metric between two images, as returned by `summary`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// mean squared error
+    pub mse: Metric,
+    /// root mean squared error
+    pub rmse: Metric,
+    /// peak signal to noise ratio in decibels
+    pub psnr: Metric,
+    /// mean absolute error
+    pub mean_absolute_error: Metric,
+    /// largest absolute error
+    pub max_error: Metric,
+    /// structural similarity index
+    pub ssim: Metric,
+    /// multi-scale structural similarity index
+    pub ms_ssim: Metric,
+}
+
+/// Computes the mean squared error between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm(vec![10, 10, 10, 10], 255, 2, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![10, 10, 10, 14], 255, 2, 2).unwrap();
+/// assert_eq!(compare::mse(&a, &b).unwrap().combined, 4.0);
+/// ```
+pub fn mse(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let channels = planes_a.iter()
+                           .zip(planes_b.iter())
+                           .map(|(pa, pb)| mean(pa.iter().zip(pb.iter()).map(|(x, y)| (x - y).powi(2))))
+                           .collect::<Vec<f64>>();
+    let combined = mean(channels.iter().copied());
+    Ok(Metric { channels, combined })
+}
+
+/// Computes the root mean squared error between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm(vec![10, 10, 10, 10], 255, 2, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![10, 10, 10, 14], 255, 2, 2).unwrap();
+/// assert_eq!(compare::rmse(&a, &b).unwrap().combined, 2.0);
+/// ```
+pub fn rmse(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let squared = mse(a, b)?;
+    Ok(Metric { channels: squared.channels.iter().map(|v| v.sqrt()).collect(), combined: squared.combined.sqrt() })
+}
+
+/// Computes the peak signal to noise ratio between two images, in decibels
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Notes
+/// - the peak is the saturation of the images (1 for PBM)
+/// - identical images return positive infinity
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm(vec![0, 0, 0, 0], 255, 2, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![0, 0, 0, 255], 255, 2, 2).unwrap();
+/// assert!((compare::psnr(&a, &b).unwrap().combined - 6.0206).abs() < 0.001);
+/// assert_eq!(compare::psnr(&a, &a).unwrap().combined, f64::INFINITY);
+/// ```
+pub fn psnr(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let squared = mse(a, b)?;
+    let peak = a.max_value() as f64;
+    let decibels = |mse: f64| if mse == 0.0 { f64::INFINITY } else { 10.0 * (peak * peak / mse).log10() };
+    Ok(Metric { channels: squared.channels.iter().map(|&v| decibels(v)).collect(), combined: decibels(squared.combined) })
+}
+
+/// Computes the mean absolute error between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm(vec![10, 10, 10, 10], 255, 2, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![12, 10, 10, 6], 255, 2, 2).unwrap();
+/// assert_eq!(compare::mean_absolute_error(&a, &b).unwrap().combined, 1.5);
+/// ```
+pub fn mean_absolute_error(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let channels = planes_a.iter()
+                           .zip(planes_b.iter())
+                           .map(|(pa, pb)| mean(pa.iter().zip(pb.iter()).map(|(x, y)| (x - y).abs())))
+                           .collect::<Vec<f64>>();
+    let combined = mean(channels.iter().copied());
+    Ok(Metric { channels, combined })
+}
+
+/// Computes the largest absolute difference between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::ppm(vec![10, 20, 30], 255, 1, 1).unwrap();
+/// let b = AnymapImage::ppm(vec![10, 25, 20], 255, 1, 1).unwrap();
+/// let max_error = compare::max_error(&a, &b).unwrap();
+/// assert_eq!(max_error.channels, vec![0.0, 5.0, 10.0]);
+/// assert_eq!(max_error.combined, 10.0);
+/// ```
+pub fn max_error(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let channels = planes_a.iter()
+                           .zip(planes_b.iter())
+                           .map(|(pa, pb)| pa.iter().zip(pb.iter()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max))
+                           .collect::<Vec<f64>>();
+    let combined = channels.iter().copied().fold(0.0, f64::max);
+    Ok(Metric { channels, combined })
+}
+
+/// Computes the structural similarity index (SSIM) between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Notes
+/// - uses a gaussian window with a sigma of 1.5 and the constants `K1 = 0.01` and `K2 = 0.03`
+/// - returns 1 for identical images, lower values for less similar images
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm((0..64).map(|i| (i * 4) as u8).collect(), 255, 8, 8).unwrap();
+/// let b = AnymapImage::pgm((0..64).map(|i| 255 - (i * 4) as u8).collect(), 255, 8, 8).unwrap();
+/// assert!((compare::ssim(&a, &a).unwrap().combined - 1.0).abs() < 1e-9);
+/// assert!(compare::ssim(&a, &b).unwrap().combined < 0.0);
+/// ```
+pub fn ssim(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let peak = a.max_value() as f64;
+    let channels = planes_a.iter()
+                           .zip(planes_b.iter())
+                           .map(|(pa, pb)| {
+                               let (luminance, contrast_structure) = ssim_components(pa, pb, a.width, a.height, peak);
+                               mean(luminance.iter().zip(contrast_structure.iter()).map(|(l, cs)| l * cs))
+                           })
+                           .collect::<Vec<f64>>();
+    let combined = mean(channels.iter().copied());
+    Ok(Metric { channels, combined })
+}
+
+/// Computes the multi-scale structural similarity index (MS-SSIM) between two images
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Notes
+/// - compares the images at up to 5 scales, halving them each time, with the weights of Wang et al. (2003)
+/// - small images use fewer scales (down to a single scale, where it equals SSIM), with the weights renormalized
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::pgm((0..32*32).map(|i| (i % 251) as u8).collect(), 255, 32, 32).unwrap();
+/// assert!((compare::ms_ssim(&a, &a).unwrap().combined - 1.0).abs() < 1e-9);
+/// ```
+pub fn ms_ssim(a: &AnymapImage, b: &AnymapImage) -> Result<Metric, String> {
+    const WEIGHTS: [f64; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let peak = a.max_value() as f64;
+
+    // keep halving while the smallest side still holds a few gaussian windows
+    let mut scales = 1;
+    while scales < WEIGHTS.len() && a.width.min(a.height) >> scales >= 8 { scales += 1; }
+    let total_weight: f64 = WEIGHTS[..scales].iter().sum();
+    let weights = WEIGHTS[..scales].iter().map(|w| w / total_weight).collect::<Vec<f64>>();
+
+    let channels = planes_a.into_iter()
+                           .zip(planes_b)
+                           .map(|(mut pa, mut pb)| {
+                               let (mut width, mut height) = (a.width, a.height);
+                               let mut score = 1.0;
+                               for (scale, weight) in weights.iter().enumerate() {
+                                   let (luminance, contrast_structure) = ssim_components(&pa, &pb, width, height, peak);
+                                   score *= mean(contrast_structure.iter().copied()).max(0.0).powf(*weight);
+                                   if scale + 1 == scales {
+                                       score *= mean(luminance.iter().copied()).max(0.0).powf(*weight);
+                                   } else {
+                                       pa = downsample(&pa, width, height);
+                                       pb = downsample(&pb, width, height);
+                                       (width, height) = (width / 2, height / 2);
+                                   }
+                               }
+                               score
+                           })
+                           .collect::<Vec<f64>>();
+    let combined = mean(channels.iter().copied());
+    Ok(Metric { channels, combined })
+}
+
+/// Computes every comparison metric between two images at once
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare;
+///
+/// let a = AnymapImage::ppm(vec![10; 8*8*3], 255, 8, 8).unwrap();
+/// let results = compare::summary(&a, &a).unwrap();
+/// assert_eq!(results.mse.combined, 0.0);
+/// assert_eq!(results.psnr.channels.len(), 3);
+/// ```
+pub fn summary(a: &AnymapImage, b: &AnymapImage) -> Result<Comparison, String> {
+    Ok(Comparison {
+        mse: mse(a, b)?,
+        rmse: rmse(a, b)?,
+        psnr: psnr(a, b)?,
+        mean_absolute_error: mean_absolute_error(a, b)?,
+        max_error: max_error(a, b)?,
+        ssim: ssim(a, b)?,
+        ms_ssim: ms_ssim(a, b)?,
+    })
+}
+
+// One floating point plane per color channel
+pub(crate) type Planes = Vec<Vec<f64>>;
+
+// Helper function checking that two images can be compared and returning their channel planes
+pub(crate) fn comparable_planes(a: &AnymapImage, b: &AnymapImage) -> Result<(Planes, Planes), String> {
+    if a.magic_number != b.magic_number { return Err(format!("Error: cannot compare images of different formats ({} & {})", a.magic_number, b.magic_number)) }
+    if a.dimensions() != b.dimensions() { return Err(format!("Error: cannot compare images of different dimensions ({:?} & {:?})", a.dimensions(), b.dimensions())) }
+    if a.max_value() != b.max_value() { return Err(format!("Error: cannot compare images of different saturation ({} & {})", a.saturation, b.saturation)) }
+    if a.buffer.is_empty() { return Err("Error: cannot compare empty images".to_string()) }
+    Ok((a.to_planes(), b.to_planes()))
+}
+
+// Helper function returning the mean of an iterator, or 0 when it is empty
+fn mean<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let (total, count) = values.fold((0.0, 0usize), |(total, count), v| (total + v, count + 1));
+    if count == 0 { 0.0 } else { total / count as f64 }
+}
+
+// Helper function computing the per pixel luminance and contrast-structure terms of SSIM
+fn ssim_components(a: &[f64], b: &[f64], width: usize, height: usize, peak: f64) -> (Vec<f64>, Vec<f64>) {
+    let (c1, c2) = ((0.01 * peak).powi(2), (0.03 * peak).powi(2));
+    let blur = |plane: &[f64]| gaussian_blur_plane(plane, width, height, 1.5);
+    let product = |x: &[f64], y: &[f64]| x.iter().zip(y.iter()).map(|(p, q)| p * q).collect::<Vec<f64>>();
+
+    let (mean_a, mean_b) = (blur(a), blur(b));
+    let (moment_aa, moment_bb, moment_ab) = (blur(&product(a, a)), blur(&product(b, b)), blur(&product(a, b)));
+
+    let mut luminance = Vec::with_capacity(a.len());
+    let mut contrast_structure = Vec::with_capacity(a.len());
+    for idx in 0..a.len() {
+        let (ma, mb) = (mean_a[idx], mean_b[idx]);
+        let variance_a = moment_aa[idx] - ma * ma;
+        let variance_b = moment_bb[idx] - mb * mb;
+        let covariance = moment_ab[idx] - ma * mb;
+        luminance.push((2.0 * ma * mb + c1) / (ma * ma + mb * mb + c1));
+        contrast_structure.push((2.0 * covariance + c2) / (variance_a + variance_b + c2));
+    }
+    (luminance, contrast_structure)
+}
+
+// Helper function halving a plane by averaging every 2x2 block (odd trailing rows and columns are dropped)
+fn downsample(plane: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let (new_width, new_height) = (width / 2, height / 2);
+    let mut new_plane = Vec::with_capacity(new_width * new_height);
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let at = |dy: usize, dx: usize| plane[(2*y + dy)*width + 2*x + dx];
+            new_plane.push((at(0, 0) + at(0, 1) + at(1, 0) + at(1, 1)) / 4.0);
+        }
+    }
+    new_plane
+}
+
+/// Ways of drawing the image returned by `diff_image`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStyle {
+    /// absolute difference of each pixel on a black, red, yellow, white ramp (pixels within tolerance stay black)
+    Heatmap,
+    /// greyscaled first image with every differing pixel painted red
+    Overlay,
+}
+
+/// Options used by `diff_image`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// how the difference image is drawn
+    pub style: DiffStyle,
+    /// largest per channel difference that still counts as equal
+    pub tolerance: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { style: DiffStyle::Heatmap, tolerance: 0 }
+    }
+}
+
+/// Result of `diff_image`
+#[derive(Debug, Clone)]
+pub struct Difference {
+    /// 8 bit PPM image highlighting the differing pixels
+    pub image: AnymapImage,
+    /// amount of pixels with a channel differing by more than the tolerance
+    pub differing_pixels: usize,
+    /// smallest ((top, left), (bottom, right)) box holding every differing pixel, inclusive, or `None` when no pixel differs
+    pub bounding_box: Option<((usize, usize), (usize, usize))>,
+}
+
+/// Builds an image showing where two images differ, along with the amount of differing pixels and their bounding box
+///
+/// # Rules for arguments:
+/// - both images must share the same format, dimensions and saturation
+///
+/// # Notes
+/// - a pixel differs when any of its channels differs by more than `options.tolerance`
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::compare::{self, DiffOptions, DiffStyle};
+///
+/// let a = AnymapImage::pgm(vec![10, 10, 10, 10, 10, 10], 255, 2, 3).unwrap();
+/// let b = AnymapImage::pgm(vec![10, 12, 10, 10, 10, 90], 255, 2, 3).unwrap();
+///
+/// let diff = compare::diff_image(&a, &b, &DiffOptions { style: DiffStyle::Overlay, tolerance: 5 }).unwrap();
+/// assert_eq!(diff.differing_pixels, 1);
+/// assert_eq!(diff.bounding_box, Some(((1, 2), (1, 2))));
+/// assert_eq!(&diff.image.get_buffer()[15..18], &[255, 0, 0]);
+/// ```
+pub fn diff_image(a: &AnymapImage, b: &AnymapImage, options: &DiffOptions) -> Result<Difference, String> {
+    let (planes_a, planes_b) = comparable_planes(a, b)?;
+    let peak = a.max_value() as f64;
+    let tolerance = options.tolerance as f64;
+
+    let differences = (0..a.width * a.height).map(|idx| planes_a.iter()
+                                                                .zip(planes_b.iter())
+                                                                .map(|(pa, pb)| (pa[idx] - pb[idx]).abs())
+                                                                .fold(0.0, f64::max))
+                                             .collect::<Vec<f64>>();
+
+    let mut differing_pixels = 0;
+    let mut bounding_box: Option<((usize, usize), (usize, usize))> = None;
+    let mut buffer = Vec::with_capacity(differences.len() * 3);
+    let base = a.greyscale_base();
+    for (idx, &difference) in differences.iter().enumerate() {
+        let differs = difference > tolerance;
+        if differs {
+            differing_pixels += 1;
+            let (y, x) = (idx / a.width, idx % a.width);
+            bounding_box = Some(match bounding_box {
+                Some(((top, left), (bottom, right))) => ((top.min(y), left.min(x)), (bottom.max(y), right.max(x))),
+                None => ((y, x), (y, x)),
+            });
+        }
+
+        let pixel = match options.style {
+            DiffStyle::Heatmap => {
+                let t = if differs && peak > 0.0 { difference / peak } else { 0.0 };
+                [t * 3.0, t * 3.0 - 1.0, t * 3.0 - 2.0].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+            },
+            DiffStyle::Overlay => if differs { [255, 0, 0] } else { [base[idx]; 3] },
+        };
+        buffer.extend_from_slice(&pixel);
+    }
+
+    Ok(Difference {
+        image: AnymapImage::ppm(buffer, 255, a.height, a.width)?,
+        differing_pixels,
+        bounding_box,
+    })
+}
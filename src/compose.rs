@@ -0,0 +1,301 @@
+use crate::AnymapImage;
+use crate::draw::{self, Color, TextStyle};
+
+/// How pasted pixels are combined with the pixels they cover
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// pasted pixels replace the covered ones
+    Replace,
+    /// pasted pixels are mixed in with the given opacity, in the range of `0.0..=1.0`
+    Alpha(f64),
+    /// values are added, capped at the saturation
+    Add,
+    /// values are multiplied, darkening the image (white leaves it unchanged)
+    Multiply,
+    /// inverted values are multiplied, lightening the image (black leaves it unchanged)
+    Screen,
+    /// absolute difference of the values
+    Difference,
+    /// smaller of both values
+    Darken,
+    /// larger of both values
+    Lighten,
+}
+
+impl BlendMode {
+    // Helper function combining a covered value `a` with a pasted value `b`, both in the range of `0..=max`
+    fn blend(&self, a: f64, b: f64, max: f64) -> f64 {
+        match self {
+            BlendMode::Replace => b,
+            BlendMode::Alpha(opacity) => a * (1.0 - opacity) + b * opacity,
+            BlendMode::Add => (a + b).min(max),
+            BlendMode::Multiply => if max > 0.0 { a * b / max } else { 0.0 },
+            BlendMode::Screen => if max > 0.0 { max - (max - a) * (max - b) / max } else { 0.0 },
+            BlendMode::Difference => (a - b).abs(),
+            BlendMode::Darken => a.min(b),
+            BlendMode::Lighten => a.max(b),
+        }
+    }
+}
+
+impl AnymapImage {
+
+    /// Copies an image onto the AnymapImage struct with its top left corner at (x, y)
+    ///
+    /// # Rules for arguments:
+    /// - `x` and `y` may be negative or reach past the edges, only the overlapping part is copied
+    ///
+    /// # Notes
+    /// - a source of another format or saturation is converted first (see `paste_with`)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut canvas = AnymapImage::pgm(vec![0; 3*3], 255, 3, 3).unwrap();
+    /// let patch = AnymapImage::pgm(vec![9; 2*2], 255, 2, 2).unwrap();
+    ///
+    /// canvas.paste(&patch, -1, 2).unwrap();
+    /// assert_eq!(canvas.get_buffer(), vec![0, 0, 0,
+    ///                                      0, 0, 0,
+    ///                                      9, 0, 0]);
+    /// ```
+    pub fn paste(&mut self, src: &AnymapImage, x: isize, y: isize) -> Result<(), String> {
+        self.paste_with(src, x, y, None, BlendMode::Replace)
+    }
+
+    /// Combines an image with the AnymapImage struct with its top left corner at (x, y), through an optional mask and a blend mode
+    ///
+    /// # Rules for arguments:
+    /// - `x` and `y` may be negative or reach past the edges, only the overlapping part is combined
+    /// - `mask`, when given, must be a PBM with the dimensions of `src`, only its black (1) pixels are pasted
+    /// - `BlendMode::Alpha` opacity must fall in the range of `0.0..=1.0`
+    ///
+    /// # Notes
+    /// - a source of another format or saturation is converted first: to RGB for PPM, to luminance for PGM, and to
+    ///   black below half luminance for PBM, all rescaled to the saturation of the AnymapImage struct
+    /// - PBM values are blended as numbers, 1 being black
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, BlendMode};
+    ///
+    /// let mut canvas = AnymapImage::ppm(vec![100; 2*2*3], 255, 2, 2).unwrap();
+    /// let marker = AnymapImage::pgm(vec![200; 2*2], 255, 2, 2).unwrap();
+    /// let mask = AnymapImage::pbm(vec![1, 0, 0, 1], 2, 2).unwrap();
+    ///
+    /// canvas.paste_with(&marker, 0, 0, Some(&mask), BlendMode::Alpha(0.5)).unwrap();
+    /// assert_eq!(canvas.get_buffer(), vec![150, 150, 150, 100, 100, 100,
+    ///                                      100, 100, 100, 150, 150, 150]);
+    /// ```
+    pub fn paste_with(&mut self, src: &AnymapImage, x: isize, y: isize, mask: Option<&AnymapImage>, blend: BlendMode) -> Result<(), String> {
+        if let Some(mask) = mask {
+            if mask.magic_number != "pbm" { return Err("Error: paste mask must be a pbm image".to_string()) }
+            if mask.dimensions() != src.dimensions() { return Err(format!("Error: paste mask dimensions {:?} do not match source dimensions {:?}", mask.dimensions(), src.dimensions())) }
+        }
+        if let BlendMode::Alpha(opacity) = blend {
+            if !(0.0..=1.0).contains(&opacity) { return Err(format!("Error: opacity must fall in the range of 0.0..=1.0 ({})", opacity)) }
+        }
+
+        let values = converted(src, &self.magic_number, self.max_value());
+        let (channels, max) = (self.channels(), self.max_value() as f64);
+        for row in 0..src.height {
+            let target_y = y + row as isize;
+            if target_y < 0 || target_y >= self.height as isize { continue; }
+            for column in 0..src.width {
+                let target_x = x + column as isize;
+                if target_x < 0 || target_x >= self.width as isize { continue; }
+                if mask.is_some_and(|mask| mask.buffer[row*src.width + column] == 0) { continue; }
+
+                let from = (row*src.width + column) * channels;
+                let to = (target_y as usize * self.width + target_x as usize) * channels;
+                for c in 0..channels {
+                    let value = blend.blend(self.buffer[to + c] as f64, values[from + c] as f64, max);
+                    self.buffer[to + c] = value.round().clamp(0.0, max) as u8;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Tiles images into a grid, row by row, with an optional label under every image
+    ///
+    /// # Rules for arguments:
+    /// - `images` must not be empty and `columns` must be greater than 0
+    /// - `labels`, when given, must hold one label per image
+    /// - `background` is the 8 bit RGB color of the spacing and of unused cell space
+    ///
+    /// # Notes
+    /// - every cell is as large as the largest image, smaller images are centered horizontally and aligned to the top
+    /// - images sharing a format and saturation keep it, otherwise every image is promoted to an 8 bit PPM
+    /// - labels are drawn with the small font in black or white, whichever stands out on the background, and cut to the cell width
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mask = AnymapImage::pbm(vec![1; 8*8], 8, 8).unwrap();
+    /// let depth = AnymapImage::pgm(vec![128; 4*10], 255, 4, 10).unwrap();
+    /// let frame = AnymapImage::ppm(vec![255; 6*6*3], 255, 6, 6).unwrap();
+    ///
+    /// let sheet = AnymapImage::montage(&[mask, depth, frame], 2, 2, [40, 40, 40], Some(&["mask", "depth", "frame"])).unwrap();
+    ///
+    /// // 2 columns and 2 rows of 10x18 cells (8 pixels high images and labels), with 2 pixels of spacing
+    /// assert_eq!(sheet.dimensions(), (2*18 + 3*2, 2*10 + 3*2));
+    /// ```
+    pub fn montage(images: &[AnymapImage], columns: usize, spacing: usize, background: [u8; 3], labels: Option<&[&str]>) -> Result<AnymapImage, String> {
+        if images.is_empty() { return Err("Error: montage needs at least one image".to_string()) }
+        if columns == 0 { return Err("Error: montage needs at least one column".to_string()) }
+        if let Some(labels) = labels {
+            if labels.len() != images.len() { return Err(format!("Error: montage needs one label per image ({} labels for {} images)", labels.len(), images.len())) }
+        }
+
+        let style = TextStyle::default();
+        let label_height = if labels.is_some() { draw::measure_text("0", &style).1 + 3 } else { 0 };
+        let cell_width = images.iter().map(|image| image.width).max().unwrap_or(0);
+        let cell_height = images.iter().map(|image| image.height).max().unwrap_or(0) + label_height;
+        let used_columns = columns.min(images.len());
+        let rows = images.len().div_ceil(columns);
+
+        let placements = images.iter()
+                               .enumerate()
+                               .map(|(idx, image)| (spacing + (idx % columns)*(cell_width + spacing) + (cell_width - image.width)/2,
+                                                    spacing + (idx / columns)*(cell_height + spacing)))
+                               .collect::<Vec<(usize, usize)>>();
+        let mut sheet = compose(images, &placements, used_columns*cell_width + (used_columns + 1)*spacing, rows*cell_height + (rows + 1)*spacing, background)?;
+
+        if let Some(labels) = labels {
+            let light_background = 0.299 * background[0] as f64 + 0.587 * background[1] as f64 + 0.114 * background[2] as f64 >= 128.0;
+            let max = sheet.max_value() as u8;
+            let color = match sheet.magic_number.as_str() {
+                "pbm" => Color::Bit(light_background as u8),
+                "pgm" => Color::Grey(if light_background { 0 } else { max }),
+                _ => Color::Rgb(if light_background { [0; 3] } else { [max; 3] }),
+            };
+            let fitting = (cell_width + 1) / 6;
+            for (idx, label) in labels.iter().enumerate() {
+                let label = label.chars().take(fitting).collect::<String>();
+                let width = draw::measure_text(&label, &style).0;
+                let x = spacing + (idx % columns)*(cell_width + spacing) + (cell_width - width)/2;
+                let y = placements[idx].1 + images[idx].height + 2;
+                sheet.draw_text_with(x as isize, y as isize, &label, color, &style)?;
+            }
+        }
+        Ok(sheet)
+    }
+
+    /// Joins images side by side from left to right, aligned to their top edge
+    ///
+    /// # Rules for arguments:
+    /// - `images` must not be empty
+    /// - `background` is the 8 bit RGB color of the spacing and of the space below shorter images
+    ///
+    /// # Notes
+    /// - images sharing a format and saturation keep it, otherwise every image is promoted to an 8 bit PPM
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let left = AnymapImage::pgm(vec![10; 2*2], 255, 2, 2).unwrap();
+    /// let right = AnymapImage::pgm(vec![20; 1*1], 255, 1, 1).unwrap();
+    ///
+    /// let joined = AnymapImage::hconcat(&[left, right], 1, [255, 255, 255]).unwrap();
+    /// assert_eq!(joined.get_buffer(), vec![10, 10, 255, 20,
+    ///                                      10, 10, 255, 255]);
+    /// ```
+    pub fn hconcat(images: &[AnymapImage], spacing: usize, background: [u8; 3]) -> Result<AnymapImage, String> {
+        concat(images, true, spacing, 0.0, background)
+    }
+
+    /// Stacks images from top to bottom, aligned to their left edge
+    ///
+    /// # Rules for arguments:
+    /// - `images` must not be empty
+    /// - `background` is the 8 bit RGB color of the spacing and of the space right of narrower images
+    ///
+    /// # Notes
+    /// - images sharing a format and saturation keep it, otherwise every image is promoted to an 8 bit PPM
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let top = AnymapImage::pbm(vec![1, 1], 1, 2).unwrap();
+    /// let bottom = AnymapImage::pgm(vec![51], 255, 1, 1).unwrap();
+    ///
+    /// // mixed formats are promoted to PPM
+    /// let stacked = AnymapImage::vconcat(&[top, bottom], 0, [255, 0, 0]).unwrap();
+    /// assert_eq!(stacked.get_buffer(), vec![0, 0, 0,    0, 0, 0,
+    ///                                       51, 51, 51, 255, 0, 0]);
+    /// ```
+    pub fn vconcat(images: &[AnymapImage], spacing: usize, background: [u8; 3]) -> Result<AnymapImage, String> {
+        concat(images, false, spacing, 0.0, background)
+    }
+}
+
+// Helper function joining images along one axis (side by side when `horizontal`, stacked otherwise)
+// Each image sits at `align` of the space left on the other axis (0.0 for the top or left edge, 0.5 centered, 1.0 for the bottom or right edge)
+pub(crate) fn concat(images: &[AnymapImage], horizontal: bool, spacing: usize, align: f64, background: [u8; 3]) -> Result<AnymapImage, String> {
+    if images.is_empty() { return Err("Error: concatenation needs at least one image".to_string()) }
+    let extent = |image: &AnymapImage| if horizontal { (image.width, image.height) } else { (image.height, image.width) };
+    let thickness = images.iter().map(|image| extent(image).1).max().unwrap_or(0);
+
+    let mut placements = Vec::with_capacity(images.len());
+    let mut offset = 0;
+    for image in images {
+        let (along, across) = extent(image);
+        let shift = ((thickness - across) as f64 * align) as usize;
+        placements.push(if horizontal { (offset, shift) } else { (shift, offset) });
+        offset += along + spacing;
+    }
+    let length = offset - spacing;
+    if horizontal {
+        compose(images, &placements, length, thickness, background)
+    } else {
+        compose(images, &placements, thickness, length, background)
+    }
+}
+
+// Helper function copying every image onto a new canvas filled with the background at its (x, y) placement
+// The canvas keeps the format of the images when they all share it, and is an 8 bit PPM otherwise
+fn compose(images: &[AnymapImage], placements: &[(usize, usize)], width: usize, height: usize, background: [u8; 3]) -> Result<AnymapImage, String> {
+    let first = &images[0];
+    let shared = images.iter().all(|image| image.magic_number == first.magic_number && image.max_value() == first.max_value());
+    let luminance = 0.299 * background[0] as f64 + 0.587 * background[1] as f64 + 0.114 * background[2] as f64;
+
+    let mut canvas = match (shared, first.magic_number.as_str()) {
+        (true, "pbm") => AnymapImage::pbm(vec![(luminance < 128.0) as u8; width*height], height, width)?,
+        (true, "pgm") => {
+            let grey = (luminance * first.saturation as f64 / 255.0).round() as u8;
+            AnymapImage::pgm(vec![grey; width*height], first.saturation, height, width)?
+        },
+        (true, _) => {
+            let color = background.map(|c| (c as f64 * first.saturation as f64 / 255.0).round() as u8);
+            AnymapImage::ppm(color.repeat(width*height), first.saturation, height, width)?
+        },
+        (false, _) => AnymapImage::ppm(background.repeat(width*height), 255, height, width)?,
+    };
+
+    let channels = canvas.channels();
+    for (image, &(x, y)) in images.iter().zip(placements) {
+        if image.width == 0 { continue; }
+        let buffer = converted(image, &canvas.magic_number, canvas.max_value());
+        for (row, line) in buffer.chunks(image.width * channels).enumerate() {
+            let start = ((y + row)*width + x) * channels;
+            canvas.buffer[start..start + line.len()].copy_from_slice(line);
+        }
+    }
+    Ok(canvas)
+}
+
+// Helper function returning the buffer of an image converted to another format and maximum value
+pub(crate) fn converted(image: &AnymapImage, magic_number: &str, max: usize) -> Vec<u8> {
+    if image.magic_number == magic_number && image.max_value() == max { return image.buffer.clone() }
+    let scale = max as f64 / 255.0;
+    match magic_number {
+        "pbm" => image.greyscale_base().iter().map(|&v| (v < 128) as u8).collect(),
+        "pgm" => image.greyscale_base().iter().map(|&v| (v as f64 * scale).round() as u8).collect(),
+        _ => image.rgb_base().iter().map(|&v| (v as f64 * scale).round() as u8).collect(),
+    }
+}
@@ -0,0 +1,220 @@
+use crate::AnymapImage;
+
+/// Dithering algorithms used when reducing the amount of values an image can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum DitherMethod {
+    /// no dithering, every pixel is rounded to the nearest available value
+    Nearest,
+    /// Floyd-Steinberg error diffusion (4 neighbours)
+    FloydSteinberg,
+    /// Atkinson error diffusion (6 neighbours, only 3/4 of the error is kept)
+    Atkinson,
+    /// Jarvis, Judice & Ninke error diffusion (12 neighbours)
+    JarvisJudiceNinke,
+    /// Sierra error diffusion (10 neighbours)
+    Sierra,
+    /// ordered dithering with a Bayer threshold matrix of the given size (must be a power of two from 2 to 64, ie. 2, 4, 8)
+    Bayer(usize),
+}
+
+impl DitherMethod {
+    // Helper function returning the (dx, dy, weight) error diffusion kernel and its divisor
+    fn diffusion(&self) -> (&'static [(isize, usize, f64)], f64) {
+        match self {
+            DitherMethod::FloydSteinberg => (&[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)], 16.0),
+            DitherMethod::Atkinson => (&[(1, 0, 1.0), (2, 0, 1.0), (-1, 1, 1.0), (0, 1, 1.0), (1, 1, 1.0), (0, 2, 1.0)], 8.0),
+            DitherMethod::JarvisJudiceNinke => (&[(1, 0, 7.0), (2, 0, 5.0),
+                                                  (-2, 1, 3.0), (-1, 1, 5.0), (0, 1, 7.0), (1, 1, 5.0), (2, 1, 3.0),
+                                                  (-2, 2, 1.0), (-1, 2, 3.0), (0, 2, 5.0), (1, 2, 3.0), (2, 2, 1.0)], 48.0),
+            DitherMethod::Sierra => (&[(1, 0, 5.0), (2, 0, 3.0),
+                                       (-2, 1, 2.0), (-1, 1, 4.0), (0, 1, 5.0), (1, 1, 4.0), (2, 1, 2.0),
+                                       (-1, 2, 2.0), (0, 2, 3.0), (1, 2, 2.0)], 32.0),
+            DitherMethod::Nearest | DitherMethod::Bayer(_) => (&[], 1.0),
+        }
+    }
+
+    // Helper function rejecting Bayer matrices that are not a power of two between 2 and 64
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            DitherMethod::Bayer(size) if !(2..=64).contains(size) || !size.is_power_of_two() => Err(format!("Error: bayer matrix size must be a power of two from 2 to 64 ({})", size)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl AnymapImage {
+
+    /// Converts the AnymapImage struct into a PBM image, dithering its luminance with the given method
+    ///
+    /// # Rules for arguments:
+    /// - PPM images are reduced to their luminance before dithering
+    /// - PBM formats are not accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, DitherMethod};
+    ///
+    /// // a flat 50% grey becomes an even mix of black and white pixels
+    /// let pgm = AnymapImage::pgm(vec![128; 8*8], 255, 8, 8).unwrap();
+    /// let pbm = pgm.dither_to_pbm(DitherMethod::Bayer(4)).unwrap();
+    /// assert_eq!(pbm.get_buffer().iter().filter(|&&b| b == 1).count(), 32);
+    /// ```
+    pub fn dither_to_pbm(&self, method: DitherMethod) -> Result<AnymapImage, String> {
+        self.dither_to_pbm_at(method, 0.5)
+    }
+
+    // Helper function dithering to PBM with pixels at or above `threshold * saturation` becoming white
+    pub(crate) fn dither_to_pbm_at(&self, method: DitherMethod, threshold: f64) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be dithered".to_string()) }
+        method.validate()?;
+
+        let max = self.saturation as f64;
+        let mut planes = vec![self.luminance_plane()];
+        dither_planes(&mut planes, self.width, self.height, method, max, |pixel| {
+            vec![if pixel[0] >= threshold * max { max } else { 0.0 }]
+        });
+
+        let mask = planes[0].iter().map(|&value| (value == 0.0) as u8).collect();
+        AnymapImage::pbm(mask, self.height, self.width)
+    }
+
+    /// Lowers the saturation (maxval) of the AnymapImage struct, dithering every color channel with the given method
+    ///
+    /// # Rules for arguments:
+    /// - `saturation` must be greater than 0 and lesser than or equal to the current saturation
+    /// - PBM formats are not accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, DitherMethod};
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![0, 85, 170, 255], 255, 1, 4).unwrap();
+    /// pgm.reduce_saturation(3, DitherMethod::Nearest).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 1, 2, 3]);
+    /// ```
+    pub fn reduce_saturation(&mut self, saturation: usize, method: DitherMethod) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images do not have a saturation".to_string()) }
+        if saturation == 0 || saturation > self.saturation { return Err(format!("Error: saturation must fall in the range of 1..={} ({})", self.saturation, saturation)) }
+        method.validate()?;
+
+        let scale = saturation as f64 / self.saturation as f64;
+        let mut planes = self.to_planes()
+                             .into_iter()
+                             .map(|plane| plane.iter().map(|value| value * scale).collect())
+                             .collect::<Vec<Vec<f64>>>();
+        let max = saturation as f64;
+        dither_planes(&mut planes, self.width, self.height, method, 1.0, |pixel| {
+            pixel.iter().map(|value| value.round().clamp(0.0, max)).collect()
+        });
+
+        self.saturation = saturation;
+        self.set_planes(&planes);
+        Ok(())
+    }
+
+    /// Restricts every pixel of the AnymapImage struct to a color of the given palette, dithering with the given method
+    ///
+    /// # Rules for arguments:
+    /// - `palette` must hold at least one color, each channel lesser than or equal to the saturation
+    /// - only PPM formats are accepted
+    ///
+    /// # Notes
+    /// - colors are matched by euclidean distance in RGB
+    /// - ordered dithering spreads its threshold over the average spacing of a palette of that size
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, DitherMethod};
+    ///
+    /// let palette = [[0, 0, 0], [255, 0, 0], [255, 255, 255]];
+    /// let mut ppm = AnymapImage::ppm(vec![200, 30, 10, 240, 250, 230], 255, 1, 2).unwrap();
+    ///
+    /// ppm.dither_to_palette(&palette, DitherMethod::Nearest).unwrap();
+    /// assert_eq!(ppm.get_buffer(), vec![255, 0, 0, 255, 255, 255]);
+    /// ```
+    pub fn dither_to_palette(&mut self, palette: &[[u8; 3]], method: DitherMethod) -> Result<(), String> {
+        if self.magic_number != "ppm" { return Err("Error: only ppm images can be dithered to a palette".to_string()) }
+        if palette.is_empty() { return Err("Error: palette must hold at least one color".to_string()) }
+        if palette.iter().flatten().any(|&c| c as usize > self.saturation) { return Err(format!("Error: palette colors must not exceed the image saturation ({})", self.saturation)) }
+        method.validate()?;
+
+        let colors = palette.iter()
+                            .map(|color| color.iter().map(|&c| c as f64).collect::<Vec<f64>>())
+                            .collect::<Vec<Vec<f64>>>();
+        let levels = (palette.len() as f64).cbrt().round().max(2.0);
+        let spread = self.saturation as f64 / (levels - 1.0);
+
+        let mut planes = self.to_planes();
+        dither_planes(&mut planes, self.width, self.height, method, spread, |pixel| {
+            colors.iter()
+                  .min_by(|a, b| distance(a, pixel).total_cmp(&distance(b, pixel)))
+                  .cloned()
+                  .unwrap_or_else(|| pixel.to_vec())
+        });
+
+        self.set_planes(&planes);
+        Ok(())
+    }
+}
+
+// Helper function returning the squared euclidean distance between two pixels
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+// Helper function building a normalized Bayer threshold matrix with values in `-0.5..0.5`
+fn bayer_matrix(size: usize) -> Vec<f64> {
+    let mut matrix = vec![0usize];
+    let mut current = 1;
+    while current < size {
+        let next = current * 2;
+        let mut grown = vec![0; next*next];
+        for y in 0..current {
+            for x in 0..current {
+                let value = 4 * matrix[y*current + x];
+                grown[y*next + x] = value;
+                grown[y*next + x + current] = value + 2;
+                grown[(y + current)*next + x] = value + 3;
+                grown[(y + current)*next + x + current] = value + 1;
+            }
+        }
+        matrix = grown;
+        current = next;
+    }
+    matrix.iter().map(|&value| (value as f64 + 0.5) / (size * size) as f64 - 0.5).collect()
+}
+
+// Helper function quantizing every pixel of the planes in raster order
+// Error diffusion methods push the quantization error onto unvisited neighbours, ordered dithering offsets
+// each pixel by its Bayer threshold scaled to `spread` (the distance between two output values)
+fn dither_planes<F>(planes: &mut [Vec<f64>], width: usize, height: usize, method: DitherMethod, spread: f64, quantize: F)
+where F: Fn(&[f64]) -> Vec<f64> {
+    let (kernel, divisor) = method.diffusion();
+    let bayer = match method {
+        DitherMethod::Bayer(size) => Some((size, bayer_matrix(size))),
+        _ => None,
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let idx = y*width + x;
+            let mut pixel = planes.iter().map(|plane| plane[idx]).collect::<Vec<f64>>();
+            if let Some((size, matrix)) = &bayer {
+                let offset = matrix[(y % size)*size + x % size] * spread;
+                pixel.iter_mut().for_each(|value| *value += offset);
+            }
+
+            let quantized = quantize(&pixel);
+            for (c, plane) in planes.iter_mut().enumerate() {
+                let error = plane[idx] - quantized[c];
+                plane[idx] = quantized[c];
+                for &(dx, dy, weight) in kernel {
+                    let (nx, ny) = (x as isize + dx, y + dy);
+                    if nx < 0 || nx >= width as isize || ny >= height { continue; }
+                    plane[ny*width + nx as usize] += error * weight / divisor;
+                }
+            }
+        }
+    }
+}
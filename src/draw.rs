@@ -0,0 +1,708 @@
+//! Drawing primitives for annotating images
+//!
+//! Shapes are drawn in place on an `AnymapImage` with a `Color` matching its format. Points are `(x, y)` pairs,
+//! where `x` is the column and `y` the row counted from the top left corner. Coordinates may fall outside of the
+//! image, anything past its edges is clipped.
+//!
+//! ```
+//! use ductr::AnymapImage;
+//! use ductr::draw::Color;
+//!
+//! let mut frame = AnymapImage::ppm(vec![0; 64*64*3], 255, 64, 64).unwrap();
+//!
+//! frame.draw_rectangle((10, 10), (40, 30), Color::Rgb([0, 255, 0]), 2).unwrap();
+//! frame.draw_arrow((60, 60), (40, 30), Color::Rgb([255, 0, 0]), 1, 6).unwrap();
+//! frame.draw_crosshair((25, 20), 4, Color::Rgb([255, 255, 0]), 1).unwrap();
+//! frame.draw_text(12, 50, "id 7", Color::Rgb([255, 255, 255]), 1).unwrap();
+//! ```
+
+use crate::{AnymapImage, font};
+
+/// Value written to the pixels of a shape, the variant must match the format of the image drawn on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// PBM value, 1 is black and 0 is white
+    Bit(u8),
+    /// PGM value, from 0 up to the image saturation
+    Grey(u8),
+    /// PPM value, each channel from 0 up to the image saturation
+    Rgb([u8; 3]),
+}
+
+/// Embedded monospace bitmap fonts covering printable ASCII, other characters are drawn as '?'
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    /// 5x7 glyphs in a 6x8 cell
+    Small,
+    /// 8x8 glyphs in an 8x8 cell (spacing is part of the glyphs)
+    Large,
+}
+
+impl Font {
+    // Helper function returning the (width, height) of a character cell, spacing included
+    fn cell(&self) -> (usize, usize) {
+        match self {
+            Font::Small => (6, 8),
+            Font::Large => (8, 8),
+        }
+    }
+
+    // Helper function telling whether a pixel of a glyph is set, pixels in the spacing of the cell never are
+    fn pixel(&self, character: char, column: usize, row: usize) -> bool {
+        match self {
+            Font::Small => column < 5 && row < 7 && font::small_pixel(character, column, row),
+            Font::Large => font::large_pixel(character, column, row),
+        }
+    }
+}
+
+/// Options for drawing text with `draw_text_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextStyle {
+    /// font the text is drawn with
+    pub font: Font,
+    /// integer magnification of every glyph pixel, must be greater than 0
+    pub scale: usize,
+    /// color of a box filled behind the text, none leaves the background untouched
+    pub background: Option<Color>,
+    /// pixels the background box reaches past the text on every side
+    pub padding: usize,
+    /// extra pixels between lines, before scaling
+    pub line_spacing: usize,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle { font: Font::Small, scale: 1, background: None, padding: 1, line_spacing: 0 }
+    }
+}
+
+/// Measures the (width, height) in pixels a text takes up when drawn with the given style, without the background padding
+///
+/// # Notes
+/// - lines are split on `\n`, the width is that of the longest line
+/// - the trailing spacing of the last character and line is not counted
+///
+/// # Example:
+/// ```
+/// use ductr::draw::{measure_text, Font, TextStyle};
+///
+/// assert_eq!(measure_text("frame 12", &TextStyle::default()), (47, 7));
+/// assert_eq!(measure_text("a\nbc", &TextStyle { font: Font::Large, scale: 2, ..TextStyle::default() }), (32, 32));
+/// ```
+pub fn measure_text(text: &str, style: &TextStyle) -> (usize, usize) {
+    let (cell_width, cell_height) = style.font.cell();
+    let (glyph_width, glyph_height) = match style.font {
+        Font::Small => (5, 7),
+        Font::Large => (8, 8),
+    };
+    let lines = text.split('\n').collect::<Vec<&str>>();
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    if longest == 0 && lines.len() == 1 { return (0, 0) }
+
+    let width = if longest == 0 { 0 } else { (longest - 1) * cell_width + glyph_width };
+    let height = (lines.len() - 1) * (cell_height + style.line_spacing) + glyph_height;
+    (width * style.scale, height * style.scale)
+}
+
+impl AnymapImage {
+
+    /// Draws a straight line between two points with Bresenham's algorithm
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0, thicker lines are drawn with a round brush
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*3], 3, 3).unwrap();
+    /// pbm.draw_line((0, 0), (2, 2), Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 0, 0,
+    ///                                   0, 1, 0,
+    ///                                   0, 0, 1]);
+    /// ```
+    pub fn draw_line(&mut self, from: (isize, isize), to: (isize, isize), color: Color, thickness: usize) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        validate_thickness(thickness)?;
+        self.stroke_line(from, to, &values, thickness);
+        Ok(())
+    }
+
+    /// Draws an anti-aliased line between two points with Wu's algorithm, blending it into the image
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    ///
+    /// # Notes
+    /// - PBM images cannot blend, pixels covered at least halfway are set instead
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![0; 3*5], 255, 3, 5).unwrap();
+    /// pgm.draw_line_aa((0, 0), (4, 1), Color::Grey(255)).unwrap();
+    ///
+    /// // the line passes between rows 0 and 1 at its center, covering both halfway
+    /// assert_eq!(pgm.get_buffer()[2], 128);
+    /// assert_eq!(pgm.get_buffer()[5 + 2], 128);
+    /// ```
+    pub fn draw_line_aa(&mut self, from: (isize, isize), to: (isize, isize), color: Color) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        let (mut x0, mut y0, mut x1, mut y1) = (from.0 as f64, from.1 as f64, to.0 as f64, to.1 as f64);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        // only walk the columns (or rows for steep lines) that lie inside the image
+        let last = if steep { self.height } else { self.width } as isize - 1;
+        let gradient = if x1 == x0 { 0.0 } else { (y1 - y0) / (x1 - x0) };
+        for x in (x0 as isize).max(0)..=(x1 as isize).min(last) {
+            let y = y0 + gradient * (x as f64 - x0);
+            let (row, coverage) = (y.floor(), y - y.floor());
+            for (offset, alpha) in [(0, 1.0 - coverage), (1, coverage)] {
+                let (px, py) = (x, row as isize + offset);
+                if steep { self.blend_pixel(py, px, &values, alpha) } else { self.blend_pixel(px, py, &values, alpha) }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws connected line segments through a list of points
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*3], 3, 3).unwrap();
+    /// pbm.draw_polyline(&[(0, 0), (2, 0), (2, 2)], Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 1,
+    ///                                   0, 0, 1,
+    ///                                   0, 0, 1]);
+    /// ```
+    pub fn draw_polyline(&mut self, points: &[(isize, isize)], color: Color, thickness: usize) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        validate_thickness(thickness)?;
+        if let [point] = points {
+            self.stamp(point.0, point.1, &values, thickness);
+        }
+        for segment in points.windows(2) {
+            self.stroke_line(segment[0], segment[1], &values, thickness);
+        }
+        Ok(())
+    }
+
+    /// Draws the outline of a rectangle spanning two opposite corners (both included)
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0, thicker outlines grow on both sides of the border
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*4], 3, 4).unwrap();
+    /// pbm.draw_rectangle((0, 0), (3, 2), Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 1, 1,
+    ///                                   1, 0, 0, 1,
+    ///                                   1, 1, 1, 1]);
+    /// ```
+    pub fn draw_rectangle(&mut self, corner: (isize, isize), opposite: (isize, isize), color: Color, thickness: usize) -> Result<(), String> {
+        let (left, right) = (corner.0.min(opposite.0), corner.0.max(opposite.0));
+        let (top, bottom) = (corner.1.min(opposite.1), corner.1.max(opposite.1));
+        self.draw_polyline(&[(left, top), (right, top), (right, bottom), (left, bottom), (left, top)], color, thickness)
+    }
+
+    /// Fills a rectangle spanning two opposite corners (both included)
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![0; 3*3], 255, 3, 3).unwrap();
+    /// pgm.fill_rectangle((1, 1), (5, 5), Color::Grey(9)).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 0, 0,
+    ///                                   0, 9, 9,
+    ///                                   0, 9, 9]);
+    /// ```
+    pub fn fill_rectangle(&mut self, corner: (isize, isize), opposite: (isize, isize), color: Color) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        for y in corner.1.min(opposite.1).max(0)..=corner.1.max(opposite.1).min(self.height as isize - 1) {
+            self.fill_span(y, corner.0.min(opposite.0), corner.0.max(opposite.0), &values);
+        }
+        Ok(())
+    }
+
+    /// Draws the outline of a circle with the midpoint circle algorithm
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 5*5], 5, 5).unwrap();
+    /// pbm.draw_circle((2, 2), 2, Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 1, 1, 0,
+    ///                                   1, 0, 0, 0, 1,
+    ///                                   1, 0, 0, 0, 1,
+    ///                                   1, 0, 0, 0, 1,
+    ///                                   0, 1, 1, 1, 0]);
+    /// ```
+    pub fn draw_circle(&mut self, center: (isize, isize), radius: usize, color: Color, thickness: usize) -> Result<(), String> {
+        self.draw_ellipse(center, (radius, radius), color, thickness)
+    }
+
+    /// Fills a circle, including the pixels of its outline
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 5*5], 5, 5).unwrap();
+    /// pbm.fill_circle((2, 2), 2, Color::Bit(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 1, 1, 0,
+    ///                                   1, 1, 1, 1, 1,
+    ///                                   1, 1, 1, 1, 1,
+    ///                                   1, 1, 1, 1, 1,
+    ///                                   0, 1, 1, 1, 0]);
+    /// ```
+    pub fn fill_circle(&mut self, center: (isize, isize), radius: usize, color: Color) -> Result<(), String> {
+        self.fill_ellipse(center, (radius, radius), color)
+    }
+
+    /// Draws the outline of an axis aligned ellipse with the midpoint ellipse algorithm
+    ///
+    /// # Rules for arguments:
+    /// - `radii` is the (horizontal, vertical) radius
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*7], 3, 7).unwrap();
+    /// pbm.draw_ellipse((3, 1), (3, 1), Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 1, 1, 1, 1, 0,
+    ///                                   1, 0, 0, 0, 0, 0, 1,
+    ///                                   0, 1, 1, 1, 1, 1, 0]);
+    /// ```
+    pub fn draw_ellipse(&mut self, center: (isize, isize), radii: (usize, usize), color: Color, thickness: usize) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        validate_thickness(thickness)?;
+        for (x, y) in ellipse_quadrant(radii.0 as isize, radii.1 as isize) {
+            for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+                self.stamp(center.0 + sx*x, center.1 + sy*y, &values, thickness);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills an axis aligned ellipse, including the pixels of its outline
+    ///
+    /// # Rules for arguments:
+    /// - `radii` is the (horizontal, vertical) radius
+    /// - `color` must match the format of the image (see `Color`)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*7], 3, 7).unwrap();
+    /// pbm.fill_ellipse((3, 1), (3, 1), Color::Bit(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 1, 1, 1, 1, 0,
+    ///                                   1, 1, 1, 1, 1, 1, 1,
+    ///                                   0, 1, 1, 1, 1, 1, 0]);
+    /// ```
+    pub fn fill_ellipse(&mut self, center: (isize, isize), radii: (usize, usize), color: Color) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        for (x, y) in ellipse_quadrant(radii.0 as isize, radii.1 as isize) {
+            self.fill_span(center.1 - y, center.0 - x, center.0 + x, &values);
+            self.fill_span(center.1 + y, center.0 - x, center.0 + x, &values);
+        }
+        Ok(())
+    }
+
+    /// Draws the closed outline of a polygon through a list of vertices
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*3], 3, 3).unwrap();
+    /// pbm.draw_polygon(&[(0, 0), (2, 0), (0, 2)], Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 1,
+    ///                                   1, 1, 0,
+    ///                                   1, 0, 0]);
+    /// ```
+    pub fn draw_polygon(&mut self, vertices: &[(isize, isize)], color: Color, thickness: usize) -> Result<(), String> {
+        let mut closed = vertices.to_vec();
+        if let Some(&first) = vertices.first() {
+            closed.push(first);
+        }
+        self.draw_polyline(&closed, color, thickness)
+    }
+
+    /// Fills a polygon through a list of vertices with a scanline fill, including the pixels of its outline
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    ///
+    /// # Notes
+    /// - self intersecting polygons follow the even-odd rule
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 5*5], 5, 5).unwrap();
+    /// pbm.fill_polygon(&[(2, 0), (4, 2), (2, 4), (0, 2)], Color::Bit(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 0, 1, 0, 0,
+    ///                                   0, 1, 1, 1, 0,
+    ///                                   1, 1, 1, 1, 1,
+    ///                                   0, 1, 1, 1, 0,
+    ///                                   0, 0, 1, 0, 0]);
+    /// ```
+    pub fn fill_polygon(&mut self, vertices: &[(isize, isize)], color: Color) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        if vertices.is_empty() { return Ok(()) }
+
+        let top = vertices.iter().map(|v| v.1).min().unwrap_or(0).max(0);
+        let bottom = vertices.iter().map(|v| v.1).max().unwrap_or(0).min(self.height as isize - 1);
+        for y in top..=bottom {
+            let scanline = y as f64;
+            let mut crossings = Vec::new();
+            for (idx, &(x0, y0)) in vertices.iter().enumerate() {
+                let (x1, y1) = vertices[(idx + 1) % vertices.len()];
+                // half open rule, so vertices shared by two edges are only counted once
+                if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                    crossings.push(x0 as f64 + (scanline - y0 as f64) * (x1 - x0) as f64 / (y1 - y0) as f64);
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            for pair in crossings.chunks_exact(2) {
+                self.fill_span(y, pair[0].ceil() as isize, pair[1].floor() as isize, &values);
+            }
+        }
+
+        let mut closed = vertices.to_vec();
+        closed.push(vertices[0]);
+        for edge in closed.windows(2) {
+            self.stroke_line(edge[0], edge[1], &values, 1);
+        }
+        Ok(())
+    }
+
+    /// Draws a line ending in an arrow head pointing at `to`
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    /// - `head_length` is the length of both strokes of the head, which spread 30 degrees from the line
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 5*5], 5, 5).unwrap();
+    /// pbm.draw_arrow((0, 2), (4, 2), Color::Bit(1), 1, 2).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 0, 0, 0, 0,
+    ///                                   0, 0, 1, 1, 0,
+    ///                                   1, 1, 1, 1, 1,
+    ///                                   0, 0, 1, 1, 0,
+    ///                                   0, 0, 0, 0, 0]);
+    /// ```
+    pub fn draw_arrow(&mut self, from: (isize, isize), to: (isize, isize), color: Color, thickness: usize, head_length: usize) -> Result<(), String> {
+        self.draw_line(from, to, color, thickness)?;
+        let values = self.color_values(color)?;
+        if from == to { return Ok(()) }
+
+        let angle = ((from.1 - to.1) as f64).atan2((from.0 - to.0) as f64);
+        for spread in [-std::f64::consts::FRAC_PI_6, std::f64::consts::FRAC_PI_6] {
+            let tip = (to.0 + (head_length as f64 * (angle + spread).cos()).round() as isize,
+                       to.1 + (head_length as f64 * (angle + spread).sin()).round() as isize);
+            self.stroke_line(to, tip, &values, thickness);
+        }
+        Ok(())
+    }
+
+    /// Draws a crosshair marker, a horizontal and a vertical line reaching `size` pixels out from the center
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `thickness` must be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 3*3], 3, 3).unwrap();
+    /// pbm.draw_crosshair((1, 1), 1, Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 0,
+    ///                                   1, 1, 1,
+    ///                                   0, 1, 0]);
+    /// ```
+    pub fn draw_crosshair(&mut self, center: (isize, isize), size: usize, color: Color, thickness: usize) -> Result<(), String> {
+        let size = size as isize;
+        self.draw_line((center.0 - size, center.1), (center.0 + size, center.1), color, thickness)?;
+        self.draw_line((center.0, center.1 - size), (center.0, center.1 + size), color, thickness)
+    }
+
+    /// Draws text with its top left corner at (x, y) using the small font, see `draw_text_with` for more options
+    ///
+    /// # Rules for arguments:
+    /// - `color` must match the format of the image (see `Color`)
+    /// - `scale` must be greater than 0, every glyph pixel becomes a `scale` by `scale` square
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::Color;
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0; 7*5], 7, 5).unwrap();
+    /// pbm.draw_text(0, 0, "T", Color::Bit(1), 1).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 1, 1, 1,
+    ///                                   0, 0, 1, 0, 0,
+    ///                                   0, 0, 1, 0, 0,
+    ///                                   0, 0, 1, 0, 0,
+    ///                                   0, 0, 1, 0, 0,
+    ///                                   0, 0, 1, 0, 0,
+    ///                                   0, 0, 1, 0, 0]);
+    /// ```
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, color: Color, scale: usize) -> Result<(), String> {
+        self.draw_text_with(x, y, text, color, &TextStyle { scale, ..TextStyle::default() })
+    }
+
+    /// Draws text with its top left corner at (x, y) using the given font, scale and background box
+    ///
+    /// # Rules for arguments:
+    /// - `color` and the background color must match the format of the image (see `Color`)
+    /// - `style.scale` must be greater than 0
+    ///
+    /// # Notes
+    /// - `\n` starts a new line below the first character of the text
+    /// - characters outside of printable ASCII are drawn as '?'
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    /// use ductr::draw::{Color, Font, TextStyle};
+    ///
+    /// let mut ppm = AnymapImage::ppm(vec![0; 40*80*3], 255, 40, 80).unwrap();
+    /// let style = TextStyle { font: Font::Large, scale: 1, background: Some(Color::Rgb([0, 0, 128])), padding: 2, line_spacing: 2 };
+    ///
+    /// ppm.draw_text_with(4, 4, "x: 12\ny: 34", Color::Rgb([255, 255, 0]), &style).unwrap();
+    /// assert_eq!(ppm.get_buffer()[(2*80 + 2)*3..(2*80 + 2)*3 + 3].to_vec(), vec![0, 0, 128]);
+    /// ```
+    pub fn draw_text_with(&mut self, x: isize, y: isize, text: &str, color: Color, style: &TextStyle) -> Result<(), String> {
+        let values = self.color_values(color)?;
+        if style.scale == 0 { return Err("Error: text scale must be greater than 0".to_string()) }
+
+        if let Some(background) = style.background {
+            let background = self.color_values(background)?;
+            let (width, height) = measure_text(text, style);
+            let padding = style.padding as isize;
+            for row in y - padding..y + height as isize + padding {
+                self.fill_span(row, x - padding, x + width as isize + padding - 1, &background);
+            }
+        }
+
+        let (cell_width, cell_height) = style.font.cell();
+        let scale = style.scale as isize;
+        for (line_idx, line) in text.split('\n').enumerate() {
+            let top = y + (line_idx * (cell_height + style.line_spacing)) as isize * scale;
+            for (char_idx, character) in line.chars().enumerate() {
+                let left = x + (char_idx * cell_width) as isize * scale;
+                for row in 0..cell_height {
+                    for column in 0..cell_width {
+                        if !style.font.pixel(character, column, row) { continue; }
+                        let (px, py) = (left + column as isize * scale, top + row as isize * scale);
+                        for dy in 0..scale {
+                            self.fill_span(py + dy, px, px + scale - 1, &values);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Helper function checking a color against the format of the image and returning its channel values
+    pub(crate) fn color_values(&self, color: Color) -> Result<Vec<u8>, String> {
+        let values = match (self.magic_number.as_str(), color) {
+            ("pbm", Color::Bit(bit)) => vec![bit],
+            ("pgm", Color::Grey(grey)) => vec![grey],
+            ("ppm", Color::Rgb(rgb)) => rgb.to_vec(),
+            _ => return Err(format!("Error: {:?} cannot be drawn on a {} image", color, self.magic_number)),
+        };
+        if values.iter().any(|&value| value as usize > self.max_value()) { return Err(format!("Error: {:?} exceeds the maximum value of the image ({})", color, self.max_value())) }
+        Ok(values)
+    }
+
+    // Helper function writing a pixel, ignoring positions outside of the image
+    pub(crate) fn put_pixel(&mut self, x: isize, y: isize, values: &[u8]) {
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize { return }
+        let start = (y as usize * self.width + x as usize) * values.len();
+        self.buffer[start..start + values.len()].copy_from_slice(values);
+    }
+
+    // Helper function mixing a color into a pixel by `alpha` (PBM pixels are set when alpha reaches 0.5)
+    fn blend_pixel(&mut self, x: isize, y: isize, values: &[u8], alpha: f64) {
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize { return }
+        if self.magic_number == "pbm" {
+            if alpha >= 0.5 { self.put_pixel(x, y, values) }
+            return
+        }
+        let start = (y as usize * self.width + x as usize) * values.len();
+        for (old, &new) in self.buffer[start..start + values.len()].iter_mut().zip(values) {
+            *old = (*old as f64 * (1.0 - alpha) + new as f64 * alpha).round() as u8;
+        }
+    }
+
+    // Helper function filling the pixels of row `y` from column `left` to `right` (both included)
+    pub(crate) fn fill_span(&mut self, y: isize, left: isize, right: isize, values: &[u8]) {
+        if y < 0 || y >= self.height as isize { return }
+        for x in left.max(0)..=right.min(self.width as isize - 1) {
+            self.put_pixel(x, y, values);
+        }
+    }
+
+    // Helper function drawing a round brush of the given thickness centered on a pixel
+    fn stamp(&mut self, x: isize, y: isize, values: &[u8], thickness: usize) {
+        if thickness == 1 { return self.put_pixel(x, y, values) }
+        let (low, high) = (-(thickness as isize / 2), (thickness as isize - 1) / 2);
+        let center = (low + high) as f64 / 2.0;
+        let radius = thickness as f64 / 2.0;
+        for dy in low..=high {
+            for dx in low..=high {
+                if (dx as f64 - center).powi(2) + (dy as f64 - center).powi(2) <= radius * radius {
+                    self.put_pixel(x + dx, y + dy, values);
+                }
+            }
+        }
+    }
+
+    // Helper function stamping every pixel of a Bresenham line
+    fn stroke_line(&mut self, from: (isize, isize), to: (isize, isize), values: &[u8], thickness: usize) {
+        let Some((from, to)) = clip_segment(from, to, self.width, self.height, thickness as isize) else { return };
+        let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+        let (sx, sy) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+        let (mut x, mut y, mut error) = (from.0, from.1, dx + dy);
+        loop {
+            self.stamp(x, y, values, thickness);
+            if (x, y) == to { break }
+            let doubled = 2 * error;
+            if doubled >= dy { error += dy; x += sx; }
+            if doubled <= dx { error += dx; y += sy; }
+        }
+    }
+}
+
+// Helper function clipping a segment to the image grown by `margin` pixels on every side (Liang-Barsky)
+// Segments already inside are returned untouched so their pixels do not move, None means nothing is visible
+fn clip_segment(from: (isize, isize), to: (isize, isize), width: usize, height: usize, margin: isize) -> Option<((isize, isize), (isize, isize))> {
+    let (left, top, right, bottom) = (-margin, -margin, width as isize - 1 + margin, height as isize - 1 + margin);
+    let inside = |(x, y): (isize, isize)| (left..=right).contains(&x) && (top..=bottom).contains(&y);
+    if inside(from) && inside(to) { return Some((from, to)) }
+
+    let (x0, y0) = (from.0 as f64, from.1 as f64);
+    let (dx, dy) = (to.0 as f64 - x0, to.1 as f64 - y0);
+    let (mut enter, mut exit) = (0.0f64, 1.0f64);
+    for (p, q) in [(-dx, x0 - left as f64), (dx, right as f64 - x0), (-dy, y0 - top as f64), (dy, bottom as f64 - y0)] {
+        if p == 0.0 {
+            if q < 0.0 { return None }
+        } else if p < 0.0 {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+    if enter > exit { return None }
+
+    let point = |t: f64| ((x0 + t * dx).round() as isize, (y0 + t * dy).round() as isize);
+    Some((if inside(from) { from } else { point(enter) }, if inside(to) { to } else { point(exit) }))
+}
+
+// Helper function rejecting strokes without width
+fn validate_thickness(thickness: usize) -> Result<(), String> {
+    if thickness == 0 { return Err("Error: thickness must be greater than 0".to_string()) }
+    Ok(())
+}
+
+// Helper function returning the points of the lower right quadrant of an ellipse outline (midpoint ellipse algorithm)
+// The other quadrants are found by mirroring the points around the center
+fn ellipse_quadrant(rx: isize, ry: isize) -> Vec<(isize, isize)> {
+    if rx == 0 || ry == 0 {
+        return (0..=rx).map(|x| (x, 0)).chain((0..=ry).map(|y| (0, y))).collect()
+    }
+
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let mut points = Vec::new();
+    let (mut x, mut y) = (0, ry);
+
+    // region where the slope is flatter than -1, stepping along x
+    let mut decision = 4*ry2 - 4*rx2*ry + rx2;
+    while ry2 * x <= rx2 * y {
+        points.push((x, y));
+        if decision < 0 {
+            decision += 4*ry2*(2*x + 3);
+        } else {
+            decision += 4*ry2*(2*x + 3) - 8*rx2*(y - 1);
+            y -= 1;
+        }
+        x += 1;
+    }
+
+    // region where the slope is steeper than -1, stepping along y
+    let mut decision = ry2*(2*x + 1)*(2*x + 1) + 4*rx2*(y - 1)*(y - 1) - 4*rx2*ry2;
+    while y >= 0 {
+        points.push((x, y));
+        if decision > 0 {
+            decision += 4*rx2*(3 - 2*y);
+        } else {
+            decision += 8*ry2*(x + 1) + 4*rx2*(3 - 2*y);
+            x += 1;
+        }
+        y -= 1;
+    }
+    points
+}
@@ -0,0 +1,128 @@
+use std::{env, fs, path::PathBuf, sync::atomic::{AtomicUsize, Ordering}};
+use crate::AnymapImage;
+
+// environment variable setting the directory dumps are written to
+const DUMP_DIR_VAR: &str = "DUCTR_DUMP_DIR";
+
+// amount of buffers dumped so far by this process, used to name the files
+static DUMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes a byte buffer to a numbered binary PGM or PPM file so it can be looked at, returning the written path
+///
+/// # Rules for arguments:
+/// - `buffer` must hold `width * height` values (PGM) or `width * height * 3` values (PPM)
+/// - buffers are written with a saturation of 255, use `debug_dump_as` to write a PBM
+///
+/// # Notes
+/// - files are named `dump_0000.ext`, `dump_0001.ext`, ... in the directory set by `DUCTR_DUMP_DIR`, or `ductr_dumps` when unset
+/// - the counter restarts with every process, so earlier dumps get overwritten by the next run
+/// - prefer the `dump!` macro, which disappears from release builds
+///
+/// # Example:
+/// ```no_run
+/// use ductr::debug_dump;
+///
+/// // a 4x2 red image
+/// let path = debug_dump(&[255, 0, 0].repeat(8), 4, 2).unwrap();
+/// assert!(path.ends_with(".ppm"));
+/// ```
+pub fn debug_dump(buffer: &[u8], width: usize, height: usize) -> Result<String, String> {
+    let area = width * height;
+    let magic_number = if area != 0 && buffer.len() == area {
+        "pgm"
+    } else if area != 0 && buffer.len() == area * 3 {
+        "ppm"
+    } else {
+        return Err(format!("Error: buffer of length {} does not match a {}x{} image with 1 or 3 channels", buffer.len(), width, height))
+    };
+    debug_dump_as(buffer, width, height, magic_number)
+}
+
+/// Writes a byte buffer to a numbered binary file of the given format so it can be looked at, returning the written path
+///
+/// # Rules for arguments:
+/// - `magic_number` must be one of "pbm", "pgm" or "ppm", and `buffer` must fit it (see `AnymapImage::pbm`, `pgm` and `ppm`)
+/// - PGM and PPM buffers are written with a saturation of 255
+///
+/// # Notes
+/// - files are named like the ones of `debug_dump`, sharing its counter
+///
+/// # Example:
+/// ```no_run
+/// use ductr::debug_dump_as;
+///
+/// // a 2x2 mask where 1 is drawn black
+/// let path = debug_dump_as(&[1, 0, 0, 1], 2, 2, "pbm").unwrap();
+/// assert!(path.ends_with(".pbm"));
+/// ```
+pub fn debug_dump_as(buffer: &[u8], width: usize, height: usize, magic_number: &str) -> Result<String, String> {
+    if width == 0 || height == 0 { return Err(format!("Error: dumped dimensions must be larger than 0 ({}x{})", width, height)) }
+    let image = match magic_number {
+        "pbm" => AnymapImage::pbm(buffer.to_vec(), height, width)?,
+        "pgm" => AnymapImage::pgm(buffer.to_vec(), 255, height, width)?,
+        "ppm" => AnymapImage::ppm(buffer.to_vec(), 255, height, width)?,
+        _ => return Err(format!("Error: unknown format {:?}, expected pbm, pgm or ppm", magic_number)),
+    };
+
+    let directory = env::var(DUMP_DIR_VAR).map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("ductr_dumps"));
+    if let Err(e) = fs::create_dir_all(&directory) {
+        return Err(format!("Error: could not create dump directory {}: {:?}", directory.display(), e))
+    }
+
+    let count = DUMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let path = directory.join(format!("dump_{:04}.{}", count, image.magic_number));
+    let path = path.to_str().ok_or_else(|| format!("Error: dump path is not valid unicode ({})", path.display()))?;
+    image.write_as_binary(path)?;
+    Ok(path.to_string())
+}
+
+/// Dumps a byte buffer of the given width and height to a numbered image file and prints where it went
+///
+/// # Notes
+/// - expands to nothing in release builds (without `debug_assertions`) and when the `dump` feature is disabled
+/// - see `debug_dump` for how the format and file name are picked, a fourth argument picks the format like `debug_dump_as`
+/// - failures are printed instead of panicking, so a dump never stops the program being debugged
+///
+/// # Example:
+/// ```no_run
+/// let gradient = (0..=255).collect::<Vec<u8>>();
+/// ductr::dump!(gradient, 16, 16);
+///
+/// let mask = gradient.iter().map(|&v| (v < 128) as u8).collect::<Vec<u8>>();
+/// ductr::dump!(mask, 16, 16, "pbm");
+/// ```
+#[cfg(feature = "dump")]
+#[macro_export]
+macro_rules! dump {
+    ($buffer:expr, $width:expr, $height:expr $(,)?) => {
+        if cfg!(debug_assertions) {
+            match $crate::debug_dump(&$buffer, $width, $height) {
+                Ok(path) => eprintln!("[{}:{}] {} -> {}", file!(), line!(), stringify!($buffer), path),
+                Err(e) => eprintln!("[{}:{}] {} could not be dumped: {}", file!(), line!(), stringify!($buffer), e),
+            }
+        }
+    };
+    ($buffer:expr, $width:expr, $height:expr, $format:expr $(,)?) => {
+        if cfg!(debug_assertions) {
+            match $crate::debug_dump_as(&$buffer, $width, $height, $format) {
+                Ok(path) => eprintln!("[{}:{}] {} -> {}", file!(), line!(), stringify!($buffer), path),
+                Err(e) => eprintln!("[{}:{}] {} could not be dumped: {}", file!(), line!(), stringify!($buffer), e),
+            }
+        }
+    };
+}
+
+/// Dumps a byte buffer of the given width and height to a numbered image file and prints where it went
+///
+/// # Notes
+/// - the `dump` feature is disabled, so this expands to nothing: the arguments are type checked but never evaluated
+#[cfg(not(feature = "dump"))]
+#[macro_export]
+macro_rules! dump {
+    ($buffer:expr, $width:expr, $height:expr $(,)?) => {
+        if false { let _ = (&$buffer, &$width, &$height); }
+    };
+    ($buffer:expr, $width:expr, $height:expr, $format:expr $(,)?) => {
+        if false { let _ = (&$buffer, &$width, &$height, &$format); }
+    };
+}
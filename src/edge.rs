@@ -0,0 +1,199 @@
+use std::f64::consts::PI;
+use crate::AnymapImage;
+use crate::filter::{convolve_line, gaussian_blur_plane, map_lines};
+
+/// Discrete derivative operators used by `AnymapImage::gradient`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientOperator {
+    /// 3x3 Sobel operator (smoothing weights 1, 2, 1)
+    Sobel,
+    /// 3x3 Prewitt operator (smoothing weights 1, 1, 1)
+    Prewitt,
+    /// 3x3 Scharr operator (smoothing weights 3, 10, 3)
+    Scharr,
+}
+
+impl GradientOperator {
+    // Helper function returning the normalized smoothing half of the separable operator
+    fn smoothing(&self) -> [f64; 3] {
+        match self {
+            GradientOperator::Sobel => [1.0 / 4.0, 2.0 / 4.0, 1.0 / 4.0],
+            GradientOperator::Prewitt => [1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+            GradientOperator::Scharr => [3.0 / 16.0, 10.0 / 16.0, 3.0 / 16.0],
+        }
+    }
+}
+
+impl AnymapImage {
+
+    /// Computes the gradient of the AnymapImage struct, returning a (magnitude, direction) tuple of PGM images
+    ///
+    /// # Rules for arguments:
+    /// - PPM images are reduced to their luminance before differentiating
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - operators are normalized so that a sharp step of height `h` has a magnitude of `h`, clamped to the image saturation
+    /// - the direction image maps angles of `0..360` degrees (clockwise from the x axis) to `0..=255`
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, GradientOperator};
+    ///
+    /// // create 4x4 PGM AnymapImage object with a vertical step edge in the middle
+    /// let buffer = vec![0, 0, 200, 200,
+    ///                   0, 0, 200, 200,
+    ///                   0, 0, 200, 200,
+    ///                   0, 0, 200, 200];
+    /// let pgm = AnymapImage::pgm(buffer, 255, 4, 4).unwrap();
+    ///
+    /// let (magnitude, direction) = pgm.gradient(GradientOperator::Sobel).unwrap();
+    /// assert_eq!(magnitude.get_buffer()[1], 200);
+    /// assert_eq!(direction.get_buffer()[1], 0);
+    /// ```
+    pub fn gradient(&self, operator: GradientOperator) -> Result<(AnymapImage, AnymapImage), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be differentiated".to_string()) }
+
+        let (gx, gy) = gradient_planes(&self.luminance_plane(), self.width, self.height, operator);
+        let max = self.saturation as f64;
+        let magnitude = gx.iter()
+                          .zip(gy.iter())
+                          .map(|(x, y)| x.hypot(*y).round().min(max) as u8)
+                          .collect::<Vec<u8>>();
+        let direction = gx.iter()
+                          .zip(gy.iter())
+                          .map(|(x, y)| (y.atan2(*x).rem_euclid(2.0 * PI) / (2.0 * PI) * 256.0).floor().min(255.0) as u8)
+                          .collect::<Vec<u8>>();
+
+        Ok((AnymapImage::pgm(magnitude, self.saturation, self.height, self.width)?,
+            AnymapImage::pgm(direction, 255, self.height, self.width)?))
+    }
+
+    /// Applies a Laplacian of Gaussian filter to the AnymapImage struct, returning a PGM image
+    ///
+    /// # Rules for arguments:
+    /// - `sigma` is the standard deviation of the gaussian in pixels and must be greater than 0
+    /// - PPM images are reduced to their luminance before filtering
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - the response is scale normalized (multiplied by sigma squared) and offset so that a zero response is mid grey
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // flat images have no second derivative, so every pixel is mid grey
+    /// let pgm = AnymapImage::pgm(vec![100; 8*8], 255, 8, 8).unwrap();
+    /// let log = pgm.laplacian_of_gaussian(1.0).unwrap();
+    /// assert!(log.get_buffer().iter().all(|&b| b == 128));
+    /// ```
+    pub fn laplacian_of_gaussian(&self, sigma: f64) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be differentiated".to_string()) }
+        if sigma.is_nan() || sigma <= 0.0 { return Err(format!("Error: gaussian sigma must be greater than 0 ({})", sigma)) }
+
+        let smooth = gaussian_blur_plane(&self.luminance_plane(), self.width, self.height, sigma);
+        let second = [1.0, -2.0, 1.0];
+        let dxx = map_lines(&smooth, self.width, self.height, true, |line| convolve_line(line, &second));
+        let dyy = map_lines(&smooth, self.width, self.height, false, |line| convolve_line(line, &second));
+
+        let max = self.saturation as f64;
+        let mid = (max / 2.0).ceil();
+        let response = dxx.iter()
+                          .zip(dyy.iter())
+                          .map(|(xx, yy)| (mid + sigma * sigma * (xx + yy)).round().clamp(0.0, max) as u8)
+                          .collect::<Vec<u8>>();
+        AnymapImage::pgm(response, self.saturation, self.height, self.width)
+    }
+
+    /// Detects edges in the AnymapImage struct with the Canny algorithm, returning a PBM edge map
+    ///
+    /// # Rules for arguments:
+    /// - `sigma` is the standard deviation of the gaussian used to smooth the image before differentiating (0 skips smoothing)
+    /// - `low` and `high` are hysteresis thresholds on the Sobel gradient magnitude, in the same units as the pixel values
+    /// - `low` must be lesser than or equal to `high`
+    /// - PPM images are reduced to their luminance before detection
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - edge pixels are set to 1 (black) and all other pixels to 0 (white)
+    /// - pixels above `low` are only kept if they are 8-connected to a pixel above `high`
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // create 8x8 PGM AnymapImage object with a vertical step edge in the middle
+    /// let buffer = (0..8*8).map(|i| if i % 8 < 4 { 0 } else { 200 }).collect::<Vec<u8>>();
+    /// let pgm = AnymapImage::pgm(buffer, 255, 8, 8).unwrap();
+    ///
+    /// let edges = pgm.canny(1.0, 20.0, 50.0).unwrap();
+    /// assert_eq!(edges.get_buffer().iter().filter(|&&b| b == 1).count(), 8);
+    /// ```
+    pub fn canny(&self, sigma: f64, low: f64, high: f64) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be differentiated".to_string()) }
+        if sigma.is_nan() || sigma < 0.0 { return Err(format!("Error: gaussian sigma must not be negative ({})", sigma)) }
+        if low > high { return Err(format!("Error: low threshold is greater than high threshold ({} > {})", low, high)) }
+
+        let (width, height) = (self.width, self.height);
+        let mut plane = self.luminance_plane();
+        if sigma > 0.0 { plane = gaussian_blur_plane(&plane, width, height, sigma); }
+        let (gx, gy) = gradient_planes(&plane, width, height, GradientOperator::Sobel);
+        let magnitude = gx.iter().zip(gy.iter()).map(|(x, y)| x.hypot(*y)).collect::<Vec<f64>>();
+
+        // non-maximum suppression along the quantized gradient direction
+        let at = |x: isize, y: isize| {
+            if x < 0 || y < 0 || x >= width as isize || y >= height as isize { 0.0 } else { magnitude[y as usize * width + x as usize] }
+        };
+        let mut thin = vec![0.0; magnitude.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let idx = y*width + x;
+                let angle = gy[idx].atan2(gx[idx]).to_degrees().rem_euclid(180.0);
+                let (dx, dy) = match angle {
+                    a if !(22.5..157.5).contains(&a) => (1, 0),
+                    a if a < 67.5 => (1, 1),
+                    a if a < 112.5 => (0, 1),
+                    _ => (-1, 1),
+                };
+                let (xi, yi) = (x as isize, y as isize);
+                if magnitude[idx] >= at(xi + dx, yi + dy) && magnitude[idx] > at(xi - dx, yi - dy) {
+                    thin[idx] = magnitude[idx];
+                }
+            }
+        }
+
+        // hysteresis, growing strong edges through connected weak edges
+        let mut edges = vec![0u8; thin.len()];
+        let mut stack = (0..thin.len()).filter(|&idx| thin[idx] > 0.0 && thin[idx] >= high).collect::<Vec<usize>>();
+        for &idx in stack.iter() { edges[idx] = 1; }
+        while let Some(idx) = stack.pop() {
+            let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize { continue; }
+                    let neighbour = ny as usize * width + nx as usize;
+                    if edges[neighbour] == 0 && thin[neighbour] > 0.0 && thin[neighbour] >= low {
+                        edges[neighbour] = 1;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        AnymapImage::pbm(edges, height, width)
+    }
+}
+
+// Helper function computing the horizontal and vertical derivative planes of a plane
+pub(crate) fn gradient_planes(plane: &[f64], width: usize, height: usize, operator: GradientOperator) -> (Vec<f64>, Vec<f64>) {
+    let derivative = [-1.0, 0.0, 1.0];
+    let smoothing = operator.smoothing();
+
+    let dx = map_lines(plane, width, height, true, |line| convolve_line(line, &derivative));
+    let gx = map_lines(&dx, width, height, false, |line| convolve_line(line, &smoothing));
+    let dy = map_lines(plane, width, height, false, |line| convolve_line(line, &derivative));
+    let gy = map_lines(&dy, width, height, true, |line| convolve_line(line, &smoothing));
+    (gx, gy)
+}
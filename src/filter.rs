@@ -0,0 +1,264 @@
+use crate::AnymapImage;
+
+impl AnymapImage {
+
+    /// Blurs every color channel of the AnymapImage struct with a square box filter
+    ///
+    /// # Rules for arguments:
+    /// - `radius` is the distance in pixels from the center to the edge of the box (ie. 1 for a 3x3 box)
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - pixels outside of the image are treated as copies of the nearest edge pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // create grey 10x10 PGM AnymapImage object with a single white pixel
+    /// let mut buffer = vec![0; 10*10];
+    /// buffer[55] = 255;
+    /// let mut pgm = AnymapImage::pgm(buffer, 255, 10, 10).unwrap();
+    ///
+    /// // spread the white pixel over a 3x3 box
+    /// pgm.box_blur(1).unwrap();
+    /// assert_eq!(pgm.get_buffer()[44], 28);
+    /// ```
+    pub fn box_blur(&mut self, radius: usize) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be blurred".to_string()) }
+
+        let planes = self.to_planes()
+                         .iter()
+                         .map(|plane| box_blur_plane(plane, self.width, self.height, radius))
+                         .collect::<Vec<Vec<f64>>>();
+        self.set_planes(&planes);
+        Ok(())
+    }
+
+    /// Blurs every color channel of the AnymapImage struct with a gaussian filter
+    ///
+    /// # Rules for arguments:
+    /// - `sigma` is the standard deviation of the gaussian in pixels and must be greater than 0
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - small sigmas are convolved with an exact separable kernel, while large sigmas are approximated with three successive box blurs
+    /// - pixels outside of the image are treated as copies of the nearest edge pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // create 10x10 PGM AnymapImage object with a single white pixel
+    /// let mut buffer = vec![0; 10*10];
+    /// buffer[55] = 255;
+    /// let mut pgm = AnymapImage::pgm(buffer, 255, 10, 10).unwrap();
+    ///
+    /// pgm.gaussian_blur(1.0).unwrap();
+    /// assert!(pgm.get_buffer()[55] < 255);
+    /// ```
+    pub fn gaussian_blur(&mut self, sigma: f64) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be blurred".to_string()) }
+        if sigma.is_nan() || sigma <= 0.0 { return Err(format!("Error: gaussian sigma must be greater than 0 ({})", sigma)) }
+
+        let planes = self.to_planes()
+                         .iter()
+                         .map(|plane| gaussian_blur_plane(plane, self.width, self.height, sigma))
+                         .collect::<Vec<Vec<f64>>>();
+        self.set_planes(&planes);
+        Ok(())
+    }
+
+    /// Replaces every pixel of the AnymapImage struct with the median of its square neighbourhood, per color channel
+    ///
+    /// # Rules for arguments:
+    /// - `radius` is the distance in pixels from the center to the edge of the neighbourhood (ie. 1 for a 3x3 square)
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - uses sliding column histograms, so the cost per pixel does not grow with `radius`
+    /// - pixels outside of the image are treated as copies of the nearest edge pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // create black 10x10 PGM AnymapImage object with a single noisy pixel
+    /// let mut buffer = vec![0; 10*10];
+    /// buffer[55] = 255;
+    /// let mut pgm = AnymapImage::pgm(buffer, 255, 10, 10).unwrap();
+    ///
+    /// // remove the noise
+    /// pgm.median_filter(1).unwrap();
+    /// assert!(pgm.get_buffer().iter().all(|&b| b == 0));
+    /// ```
+    pub fn median_filter(&mut self, radius: usize) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be median filtered".to_string()) }
+
+        let channels = self.channels();
+        for c in 0..channels {
+            let plane = self.buffer.iter()
+                                   .skip(c)
+                                   .step_by(channels)
+                                   .copied()
+                                   .collect::<Vec<u8>>();
+            let filtered = median_plane(&plane, self.width, self.height, radius);
+            for (idx, value) in filtered.into_iter().enumerate() {
+                self.buffer[idx*channels + c] = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Helper function applying `op` to every row (or every column) of a plane
+// Returns the new plane built from the transformed lines
+pub(crate) fn map_lines<F>(plane: &[f64], width: usize, height: usize, horizontal: bool, op: F) -> Vec<f64>
+where F: Fn(&[f64]) -> Vec<f64> {
+    let mut new_plane = vec![0.0; plane.len()];
+    if horizontal {
+        for y in 0..height {
+            let line = op(&plane[y*width..(y+1)*width]);
+            new_plane[y*width..(y+1)*width].copy_from_slice(&line);
+        }
+    } else {
+        for x in 0..width {
+            let column = (0..height).map(|y| plane[y*width + x]).collect::<Vec<f64>>();
+            for (y, value) in op(&column).into_iter().enumerate() {
+                new_plane[y*width + x] = value;
+            }
+        }
+    }
+    new_plane
+}
+
+// Helper function for averaging a single line over a sliding window of `2*radius+1` values
+// Indices outside of the line are clamped to the nearest edge
+fn box_blur_line(line: &[f64], radius: usize) -> Vec<f64> {
+    if line.is_empty() { return Vec::new() }
+    let last = line.len() as isize - 1;
+    let at = |idx: isize| line[idx.clamp(0, last) as usize];
+    let r = radius as isize;
+    let size = (2*radius + 1) as f64;
+
+    let mut sum: f64 = (-r..=r).map(at).sum();
+    let mut new_line = Vec::with_capacity(line.len());
+    for x in 0..line.len() as isize {
+        new_line.push(sum / size);
+        sum += at(x + r + 1) - at(x - r);
+    }
+    new_line
+}
+
+// Helper function for box blurring a full plane as a horizontal then a vertical pass
+pub(crate) fn box_blur_plane(plane: &[f64], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    let horizontal = map_lines(plane, width, height, true, |line| box_blur_line(line, radius));
+    map_lines(&horizontal, width, height, false, |line| box_blur_line(line, radius))
+}
+
+// Helper function for convolving a single line with an odd length kernel centered on each value
+// Indices outside of the line are clamped to the nearest edge
+pub(crate) fn convolve_line(line: &[f64], kernel: &[f64]) -> Vec<f64> {
+    if line.is_empty() { return Vec::new() }
+    let last = line.len() as isize - 1;
+    let r = (kernel.len() / 2) as isize;
+    (0..line.len() as isize).map(|x| kernel.iter()
+                                           .enumerate()
+                                           .map(|(k, weight)| weight * line[(x + k as isize - r).clamp(0, last) as usize])
+                                           .sum())
+                            .collect()
+}
+
+// Helper function building a normalized 1D gaussian kernel reaching out to 3 sigma
+pub(crate) fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel = (-radius..=radius).map(|x| (-((x*x) as f64) / (2.0 * sigma * sigma)).exp())
+                                   .collect::<Vec<f64>>();
+    let total: f64 = kernel.iter().sum();
+    kernel.iter().map(|weight| weight / total).collect()
+}
+
+// Helper function computing the radii of three box blurs approximating a gaussian
+// Based on "Fast Almost-Gaussian Filtering" (P. Kovesi, 2010)
+fn boxes_for_gauss(sigma: f64) -> [usize; 3] {
+    let n = 3.0;
+    let ideal_width = (12.0 * sigma * sigma / n + 1.0).sqrt();
+    let mut lower = ideal_width.floor() as usize;
+    if lower.is_multiple_of(2) { lower -= 1; }
+    let upper = lower + 2;
+    let lower_f = lower as f64;
+    let ideal_count = (12.0 * sigma * sigma - n * lower_f * lower_f - 4.0 * n * lower_f - 3.0 * n) / (-4.0 * lower_f - 4.0);
+    let count = ideal_count.round() as usize;
+
+    let mut radii = [0; 3];
+    for (idx, radius) in radii.iter_mut().enumerate() {
+        *radius = if idx < count { (lower - 1) / 2 } else { (upper - 1) / 2 };
+    }
+    radii
+}
+
+// Helper function for gaussian blurring a full plane
+// Switches from an exact kernel to repeated box blurs once the kernel grows large
+pub(crate) fn gaussian_blur_plane(plane: &[f64], width: usize, height: usize, sigma: f64) -> Vec<f64> {
+    if sigma < 2.0 {
+        let kernel = gaussian_kernel(sigma);
+        let horizontal = map_lines(plane, width, height, true, |line| convolve_line(line, &kernel));
+        map_lines(&horizontal, width, height, false, |line| convolve_line(line, &kernel))
+    } else {
+        boxes_for_gauss(sigma).iter()
+                              .fold(plane.to_vec(), |acc, &radius| box_blur_plane(&acc, width, height, radius))
+    }
+}
+
+// Helper function for median filtering a single 8 bit plane
+// Keeps one histogram per column and a kernel histogram that slides along each row (Perreault & Hébert, 2007)
+fn median_plane(plane: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    if plane.is_empty() { return Vec::new() }
+    let r = radius as isize;
+    let clamp_x = |x: isize| x.clamp(0, width as isize - 1) as usize;
+    let clamp_y = |y: isize| y.clamp(0, height as isize - 1) as usize;
+    let half = ((2*radius + 1) * (2*radius + 1)) / 2;
+
+    // column histograms initially hold rows -r..=r of every column
+    let mut columns = vec![[0u32; 256]; width];
+    for (x, column) in columns.iter_mut().enumerate() {
+        for y in -r..=r {
+            column[plane[clamp_y(y)*width + x] as usize] += 1;
+        }
+    }
+
+    let mut new_plane = vec![0; plane.len()];
+    for y in 0..height {
+        let mut kernel = [0u32; 256];
+        for x in -r..=r {
+            for (bin, count) in kernel.iter_mut().zip(columns[clamp_x(x)].iter()) {
+                *bin += count;
+            }
+        }
+
+        for x in 0..width {
+            let mut seen = 0;
+            for (value, count) in kernel.iter().enumerate() {
+                seen += *count as usize;
+                if seen > half {
+                    new_plane[y*width + x] = value as u8;
+                    break;
+                }
+            }
+
+            // slide kernel histogram one column to the right
+            let (add, remove) = (clamp_x(x as isize + r + 1), clamp_x(x as isize - r));
+            for bin in 0..256 {
+                kernel[bin] = kernel[bin] + columns[add][bin] - columns[remove][bin];
+            }
+        }
+
+        // slide column histograms one row down
+        let (add, remove) = (clamp_y(y as isize + r + 1), clamp_y(y as isize - r));
+        for (x, column) in columns.iter_mut().enumerate() {
+            column[plane[remove*width + x] as usize] -= 1;
+            column[plane[add*width + x] as usize] += 1;
+        }
+    }
+    new_plane
+}
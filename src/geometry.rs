@@ -0,0 +1,232 @@
+use crate::AnymapImage;
+use crate::compose::converted;
+
+impl AnymapImage {
+
+    /// Resizes the AnymapImage struct to the given dimensions, keeping its format and saturation
+    ///
+    /// # Rules for arguments:
+    /// - `height` and `width` must be larger than 0, and the resized buffer must fit in memory
+    ///
+    /// # Notes
+    /// - shrinking averages every source pixel covered by a target pixel, enlarging repeats the nearest source pixel
+    /// - averaged PBM pixels become black when at least half of the covered pixels are black
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![0, 100, 200, 250], 255, 1, 4).unwrap();
+    /// assert_eq!(pgm.resize(1, 2).unwrap().get_buffer(), vec![50, 225]);
+    /// assert_eq!(pgm.resize(2, 8).unwrap().dimensions(), (2, 8));
+    /// ```
+    pub fn resize(&self, height: usize, width: usize) -> Result<AnymapImage, String> {
+        if height == 0 || width == 0 { return Err(format!("Error: resized dimensions must be larger than 0 ({}x{})", width, height)) }
+
+        let mut resized = self.clone();
+        resized.buffer = area_resize(&self.buffer, self.channels(), self.width, self.height, width, height)?;
+        (resized.height, resized.width) = (height, width);
+        Ok(resized)
+    }
+
+    /// Crops a rectangle out of the AnymapImage struct, keeping its format and saturation
+    ///
+    /// # Rules for arguments:
+    /// - `height` and `width` must be larger than 0
+    /// - the rectangle starting at (`top`, `left`) must fit inside the image
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm((0..12).collect(), 255, 3, 4).unwrap();
+    /// let cropped = pgm.crop(1, 1, 2, 2).unwrap();
+    /// assert_eq!(cropped.get_buffer(), vec![5, 6, 9, 10]);
+    /// ```
+    pub fn crop(&self, top: usize, left: usize, height: usize, width: usize) -> Result<AnymapImage, String> {
+        if height == 0 || width == 0 { return Err(format!("Error: cropped dimensions must be larger than 0 ({}x{})", width, height)) }
+        if top.checked_add(height).is_none_or(|bottom| bottom > self.height) || left.checked_add(width).is_none_or(|right| right > self.width) { return Err(format!("Error: crop rectangle must fit inside the image ({}x{})", self.width, self.height)) }
+
+        let channels = self.channels();
+        let mut cropped = self.clone();
+        cropped.buffer = self.buffer
+                             .chunks(self.width * channels)
+                             .skip(top)
+                             .take(height)
+                             .flat_map(|row| row[left*channels..(left + width)*channels].iter().copied())
+                             .collect();
+        (cropped.height, cropped.width) = (height, width);
+        Ok(cropped)
+    }
+
+    /// Mirrors the AnymapImage struct left to right
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.flip_horizontal();
+    /// assert_eq!(pgm.get_buffer(), vec![3, 2, 1, 6, 5, 4]);
+    /// ```
+    pub fn flip_horizontal(&mut self) {
+        let width = self.width;
+        self.remap(self.height, width, |y, x| (y, width - 1 - x));
+    }
+
+    /// Mirrors the AnymapImage struct top to bottom
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.flip_vertical();
+    /// assert_eq!(pgm.get_buffer(), vec![4, 5, 6, 1, 2, 3]);
+    /// ```
+    pub fn flip_vertical(&mut self) {
+        let height = self.height;
+        self.remap(height, self.width, |y, x| (height - 1 - y, x));
+    }
+
+    /// Mirrors the AnymapImage struct along its top left to bottom right diagonal, swapping its width and height
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.transpose();
+    /// assert_eq!(pgm.dimensions(), (3, 2));
+    /// assert_eq!(pgm.get_buffer(), vec![1, 4, 2, 5, 3, 6]);
+    /// ```
+    pub fn transpose(&mut self) {
+        self.remap(self.width, self.height, |y, x| (x, y));
+    }
+
+    /// Rotates the AnymapImage struct 90 degrees clockwise, swapping its width and height
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.rotate_clockwise();
+    /// assert_eq!(pgm.get_buffer(), vec![4, 1, 5, 2, 6, 3]);
+    /// ```
+    pub fn rotate_clockwise(&mut self) {
+        let height = self.height;
+        self.remap(self.width, height, |y, x| (height - 1 - x, y));
+    }
+
+    /// Rotates the AnymapImage struct 90 degrees counterclockwise, swapping its width and height
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.rotate_counterclockwise();
+    /// assert_eq!(pgm.get_buffer(), vec![3, 6, 2, 5, 1, 4]);
+    /// ```
+    pub fn rotate_counterclockwise(&mut self) {
+        let width = self.width;
+        self.remap(width, self.height, |y, x| (x, width - 1 - y));
+    }
+
+    /// Rotates the AnymapImage struct 180 degrees
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![1, 2, 3, 4, 5, 6], 255, 2, 3).unwrap();
+    /// pgm.rotate_180();
+    /// assert_eq!(pgm.get_buffer(), vec![6, 5, 4, 3, 2, 1]);
+    /// ```
+    pub fn rotate_180(&mut self) {
+        let (height, width) = (self.height, self.width);
+        self.remap(height, width, |y, x| (height - 1 - y, width - 1 - x));
+    }
+
+    // Helper function rebuilding the buffer with new dimensions, copying each (y, x) pixel from the (y, x) source pixel given by `source`
+    fn remap<F>(&mut self, height: usize, width: usize, source: F)
+    where F: Fn(usize, usize) -> (usize, usize) {
+        let channels = self.channels();
+        let mut buffer = Vec::with_capacity(self.buffer.len());
+        for y in 0..height {
+            for x in 0..width {
+                let (sy, sx) = source(y, x);
+                let start = (sy*self.width + sx) * channels;
+                buffer.extend_from_slice(&self.buffer[start..start + channels]);
+            }
+        }
+        (self.buffer, self.height, self.width) = (buffer, height, width);
+    }
+
+    /// Converts the AnymapImage struct into another format of the family
+    ///
+    /// # Rules for arguments:
+    /// - `magic_number` must be one of "pbm", "pgm" or "ppm"
+    ///
+    /// # Notes
+    /// - PPM images become PGM through their Rec. 601 luminance, and PBM by thresholding it at half brightness
+    /// - PBM images converted to PGM or PPM get a saturation of 255, other images keep their saturation
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pbm = AnymapImage::pbm(vec![1, 0], 1, 2).unwrap();
+    /// assert_eq!(pbm.to_format("ppm").unwrap().get_buffer(), vec![0, 0, 0, 255, 255, 255]);
+    ///
+    /// let pgm = AnymapImage::pgm(vec![20, 200], 255, 1, 2).unwrap();
+    /// assert_eq!(pgm.to_format("pbm").unwrap().get_buffer(), vec![1, 0]);
+    /// ```
+    pub fn to_format(&self, magic_number: &str) -> Result<AnymapImage, String> {
+        let max = match (magic_number, self.magic_number.as_str()) {
+            ("pbm", _) => 1,
+            ("pgm" | "ppm", "pbm") => 255,
+            ("pgm" | "ppm", _) => self.saturation,
+            _ => return Err(format!("Error: unknown format {:?}, expected pbm, pgm or ppm", magic_number)),
+        };
+
+        let buffer = converted(self, magic_number, max);
+        match magic_number {
+            "pbm" => AnymapImage::pbm(buffer, self.height, self.width),
+            "pgm" => AnymapImage::pgm(buffer, max, self.height, self.width),
+            _ => AnymapImage::ppm(buffer, max, self.height, self.width),
+        }
+    }
+}
+
+// Helper function resizing interleaved pixels, averaging the covered area when shrinking and repeating the nearest pixel when enlarging
+// Fails when the resized buffer would not fit in memory
+pub(crate) fn area_resize(pixels: &[u8], channels: usize, width: usize, height: usize, new_width: usize, new_height: usize) -> Result<Vec<u8>, String> {
+    let span = |target: usize, old: usize, new: usize| {
+        let start = target * old / new;
+        (start, ((target + 1) * old / new).max(start + 1))
+    };
+
+    let too_large = || format!("Error: resized image of {}x{} is too large", new_width, new_height);
+    let length = new_width.checked_mul(new_height).and_then(|area| area.checked_mul(channels)).ok_or_else(too_large)?;
+    let mut resized = Vec::new();
+    resized.try_reserve_exact(length).map_err(|_| too_large())?;
+    for ty in 0..new_height {
+        let (top, bottom) = span(ty, height, new_height);
+        for tx in 0..new_width {
+            let (left, right) = span(tx, width, new_width);
+            let mut sums = vec![0usize; channels];
+            for y in top..bottom {
+                for x in left..right {
+                    for (c, sum) in sums.iter_mut().enumerate() {
+                        *sum += pixels[(y*width + x)*channels + c] as usize;
+                    }
+                }
+            }
+            let area = (bottom - top) * (right - left);
+            resized.extend(sums.iter().map(|&sum| ((sum + area / 2) / area) as u8));
+        }
+    }
+    Ok(resized)
+}
@@ -0,0 +1,179 @@
+use crate::AnymapImage;
+
+impl AnymapImage {
+
+    /// Counts the values of every color channel of the AnymapImage struct
+    /// Returns one histogram per channel (1 for PBM & PGM, 3 for PPM), each holding `saturation + 1` bins
+    ///
+    /// # Notes
+    /// - PBM images have no saturation and always return 2 bins (white and black)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![0, 2, 2, 3], 3, 2, 2).unwrap();
+    /// assert_eq!(pgm.histogram(), vec![vec![1, 0, 2, 1]]);
+    /// ```
+    pub fn histogram(&self) -> Vec<Vec<usize>> {
+        let channels = self.channels();
+        let mut histograms = vec![vec![0; self.max_value() + 1]; channels];
+        for (idx, &value) in self.buffer.iter().enumerate() {
+            let bin = (value as usize).min(self.max_value());
+            histograms[idx % channels][bin] += 1;
+        }
+        histograms
+    }
+
+    /// Counts the values of every color channel of the AnymapImage struct cumulatively
+    /// Bin `n` of each returned histogram holds the amount of values lesser than or equal to `n`
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![0, 2, 2, 3], 3, 2, 2).unwrap();
+    /// assert_eq!(pgm.cumulative_histogram(), vec![vec![1, 1, 3, 4]]);
+    /// ```
+    pub fn cumulative_histogram(&self) -> Vec<Vec<usize>> {
+        self.histogram()
+            .iter()
+            .map(|histogram| histogram.iter()
+                                      .scan(0, |total, count| { *total += count; Some(*total) })
+                                      .collect())
+            .collect()
+    }
+
+    /// Equalizes the histogram of every color channel of the AnymapImage struct, spreading values over the full saturation range
+    ///
+    /// # Rules for arguments:
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - PPM channels are equalized independently, which may shift colors
+    /// - channels holding a single value are left untouched
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // low contrast image using only values 100..=103
+    /// let mut pgm = AnymapImage::pgm(vec![100, 101, 102, 103], 255, 2, 2).unwrap();
+    /// pgm.equalize_histogram().unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 85, 170, 255]);
+    /// ```
+    pub fn equalize_histogram(&mut self) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be equalized".to_string()) }
+
+        let channels = self.channels();
+        let max = self.saturation as f64;
+        let luts = self.cumulative_histogram()
+                       .iter()
+                       .map(|cdf| {
+                           let total = *cdf.last().unwrap_or(&0);
+                           let lowest = cdf.iter().copied().find(|&count| count > 0).unwrap_or(0);
+                           (0..cdf.len()).map(|value| {
+                                             if total == lowest { return value as u8 }
+                                             (cdf[value].saturating_sub(lowest) as f64 / (total - lowest) as f64 * max).round() as u8
+                                         })
+                                         .collect::<Vec<u8>>()
+                       })
+                       .collect::<Vec<Vec<u8>>>();
+
+        for (idx, value) in self.buffer.iter_mut().enumerate() {
+            *value = luts[idx % channels][(*value as usize).min(self.saturation)];
+        }
+        Ok(())
+    }
+
+    /// Applies contrast limited adaptive histogram equalization (CLAHE) to every color channel of the AnymapImage struct
+    ///
+    /// # Rules for arguments:
+    /// - `tiles` is the (rows, columns) grid the image is split into, each must fall in the range of `1..=` the matching image dimension
+    /// - `clip_limit` caps each histogram bin at that multiple of the average bin count and must be at least 1 (higher values allow more contrast)
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - every tile is equalized on its own, and pixels blend the mappings of the four nearest tile centers
+    /// - PPM channels are equalized independently, which may shift colors
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // dark gradient on the left half, bright gradient on the right half
+    /// let buffer = (0..16*16).map(|i| if i % 16 < 8 { 10 + (i % 8) as u8 } else { 200 + (i % 8) as u8 }).collect::<Vec<u8>>();
+    /// let mut pgm = AnymapImage::pgm(buffer, 255, 16, 16).unwrap();
+    ///
+    /// pgm.clahe((2, 2), 40.0).unwrap();
+    /// assert!(pgm.get_buffer()[7] > pgm.get_buffer()[0] + 50);
+    /// ```
+    pub fn clahe(&mut self, tiles: (usize, usize), clip_limit: f64) -> Result<(), String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be equalized".to_string()) }
+        if tiles.0 == 0 || tiles.1 == 0 || tiles.0 > self.height || tiles.1 > self.width { return Err(format!("Error: tile grid {:?} does not fit image dimensions {:?}", tiles, self.dimensions())) }
+        if clip_limit.is_nan() || clip_limit < 1.0 { return Err(format!("Error: clip limit must be at least 1 ({})", clip_limit)) }
+
+        let (width, height, channels) = (self.width, self.height, self.channels());
+        let bins = self.saturation + 1;
+        let rows = (0..=tiles.0).map(|i| i * height / tiles.0).collect::<Vec<usize>>();
+        let columns = (0..=tiles.1).map(|i| i * width / tiles.1).collect::<Vec<usize>>();
+        let centers = |bounds: &[usize]| bounds.windows(2).map(|b| (b[0] + b[1]) as f64 / 2.0 - 0.5).collect::<Vec<f64>>();
+        let (row_centers, column_centers) = (centers(&rows), centers(&columns));
+
+        for c in 0..channels {
+            let at = |y: usize, x: usize| (self.buffer[(y*width + x)*channels + c] as usize).min(self.saturation);
+
+            // build a clipped, equalized mapping for every tile
+            let mut luts = Vec::with_capacity(tiles.0 * tiles.1);
+            for ty in 0..tiles.0 {
+                for tx in 0..tiles.1 {
+                    let mut histogram = vec![0.0; bins];
+                    for y in rows[ty]..rows[ty + 1] {
+                        for x in columns[tx]..columns[tx + 1] {
+                            histogram[at(y, x)] += 1.0;
+                        }
+                    }
+                    let area = ((rows[ty + 1] - rows[ty]) * (columns[tx + 1] - columns[tx])) as f64;
+                    let limit = (clip_limit * area / bins as f64).max(1.0);
+                    let excess: f64 = histogram.iter().map(|&count| (count - limit).max(0.0)).sum();
+                    let mut total = 0.0;
+                    let lut = histogram.iter()
+                                       .map(|&count| {
+                                           total += count.min(limit) + excess / bins as f64;
+                                           total / area * self.saturation as f64
+                                       })
+                                       .collect::<Vec<f64>>();
+                    luts.push(lut);
+                }
+            }
+
+            // blend the mappings of the surrounding tile centers
+            let mut new_values = vec![0; width * height];
+            for y in 0..height {
+                let (ty0, ty1, wy) = surrounding(&row_centers, y as f64);
+                for x in 0..width {
+                    let (tx0, tx1, wx) = surrounding(&column_centers, x as f64);
+                    let value = at(y, x);
+                    let top = luts[ty0*tiles.1 + tx0][value] * (1.0 - wx) + luts[ty0*tiles.1 + tx1][value] * wx;
+                    let bottom = luts[ty1*tiles.1 + tx0][value] * (1.0 - wx) + luts[ty1*tiles.1 + tx1][value] * wx;
+                    new_values[y*width + x] = (top * (1.0 - wy) + bottom * wy).round() as u8;
+                }
+            }
+            for (idx, value) in new_values.into_iter().enumerate() {
+                self.buffer[idx*channels + c] = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Helper function finding the two tile centers around a position and the weight of the second one
+// Positions before the first or after the last center snap to that center
+fn surrounding(centers: &[f64], position: f64) -> (usize, usize, f64) {
+    let last = centers.len() - 1;
+    if position <= centers[0] { return (0, 0, 0.0) }
+    if position >= centers[last] { return (last, last, 0.0) }
+    let next = centers.iter().position(|&center| center > position).unwrap_or(last);
+    let previous = next - 1;
+    (previous, next, (position - centers[previous]) / (centers[next] - centers[previous]))
+}
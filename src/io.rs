@@ -1,6 +1,6 @@
 pub mod io {
 
-    use std::{fs::{File, self}, path::Path, io::Write};
+    use std::{fs::{File, self}, path::Path, io::{Read, Write}};
     use crate::AnymapImage;
 
     #[allow(dead_code)]
@@ -30,7 +30,25 @@ pub mod io {
                 Ok(file) => file,
                 Err(e) => return Err(format!("Error: could not create file: {:?}", e)),
             };
-    
+            self.write_ascii_to(&mut file)
+        }
+
+        /// Writes given image to any writer (ie. stdout, a socket or a byte vector) in standard ascii format
+        /// 
+        /// # Rules for arguments:
+        /// - Can take any PNM formatted file (PBM, PGM, PPM)
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let pgm = AnymapImage::pgm(vec![0, 128, 255, 64], 255, 2, 2).unwrap();
+        /// 
+        /// let mut bytes = Vec::new();
+        /// pgm.write_ascii_to(&mut bytes).unwrap();
+        /// assert_eq!(bytes, b"P2\n2 2\n255\n0 128\n255 64");
+        /// ``` 
+        pub fn write_ascii_to<W: Write>(&self, file: &mut W) -> Result<(), String> {
             // create metadata header
             let mut pixel_width: usize = 1;  // holds width of single pixel (ie. 1 for PBM & PGM, 3 for PPM)
             let mut header = String::new(); // holds header metadata
@@ -52,9 +70,9 @@ pub mod io {
                 
                 if (idx+1) != (self.width*self.height*pixel_width) {            // check if on last line, if true, do not add newline
                     if (idx+1) % (self.width*pixel_width) == 0 {                // check if index has wrote entire horizontal slice of image
-                        pixel_buffer.push_str("\n")                     // write next line to new line
+                        pixel_buffer.push('\n')                     // write next line to new line
                     } else {
-                        pixel_buffer.push_str(" ")                      // add space delim for next byte
+                        pixel_buffer.push(' ')                      // add space delim for next byte
                     }
                 }
             }
@@ -97,7 +115,25 @@ pub mod io {
                 Ok(file) => file,
                 Err(e) => return Err(format!("Error: could not create file: {:?}", e)),
             };
-            
+            self.write_binary_to(&mut file)
+        }
+
+        /// Writes given image to any writer (ie. stdout, a socket or a byte vector) in standard binary format
+        /// 
+        /// # Rules for arguments:
+        /// - Can take any PNM formatted file (PBM, PGM, PPM)
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let pgm = AnymapImage::pgm(vec![0, 128, 255, 64], 255, 2, 2).unwrap();
+        /// 
+        /// let mut bytes = Vec::new();
+        /// pgm.write_binary_to(&mut bytes).unwrap();
+        /// assert_eq!(bytes, b"P5\n2 2\n255\n\x00\x80\xff\x40");
+        /// ``` 
+        pub fn write_binary_to<W: Write>(&self, file: &mut W) -> Result<(), String> {
             // create metadata header and pixel buffer
             let mut header = String::new();                                   // holds header metadata
             let mut pixel_buffer: Vec<u8> = self.buffer.clone();                      // set new buffer to be written
@@ -115,7 +151,7 @@ pub mod io {
                                 byte = (byte << 1) + j;                               // set given u8 to bit inside new byte
                             }
                             for _ in 0..(8-y.len()) {                                 // pad byte on right if new byte holds less than 8 image bits
-                                byte = (byte << 1) + 0;                               // pad with zeros (ie. [0xFF, 0xFF] becomes C0 instead of 02)
+                                byte <<= 1;                                               // pad with zeros (ie. [0xFF, 0xFF] becomes C0 instead of 02)
                             }
                             pixel_buffer.push(byte);                                  // push byte to new buffer
                         }
@@ -164,33 +200,7 @@ pub mod io {
                 Ok(file) => file,
                 Err(e) => return Err(format!("Error: could not read file: {}", e)),
             };
-    
-            let magic_num = std::str::from_utf8(&file[..2]).map_err(|_| "Error: could not read file: magic number was not detected".to_string())?;
-            let header_args: usize;
-            match magic_num {
-                "P4" => header_args = 3,
-                "P5" | "P6" => header_args = 4,
-                _ => return Err("Error: could not read file: valid magic number was not detected".to_string()),
-            }
-
-            let (header, byte_vector) = parse_header_to_slice(&file, header_args);
-            let mut parsed_header: Vec<usize> = Vec::new();
-            for x in &header[1..] {
-                let parse_val = match x.parse::<usize>() {
-                    Ok(parse_val) => parse_val,
-                    Err(_) => return Err("The header format is not valid".to_string()), 
-                };
-                parsed_header.push(parse_val);
-            }
-    
-            let parsed_image: Result<AnymapImage, String>;
-            match magic_num {
-                "P4" => parsed_image = AnymapImage::pbm(realign_byte_buffer(byte_vector, parsed_header[0]), parsed_header[1], parsed_header[0]),
-                "P5" => parsed_image = AnymapImage::pgm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
-                "P6" => parsed_image = AnymapImage::ppm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
-                _ => unreachable!(),
-            }
-            parsed_image
+            parse_binary(&file)
         }
     
         /// Read given file to new image in standard ascii format
@@ -214,63 +224,139 @@ pub mod io {
                 Ok(file) => file,
                 Err(e) => return Err(format!("Error: could not read file: {:?}", e)),
             };
-    
-            let delim_vec = file.split_ascii_whitespace().collect::<Vec<&str>>();
+            parse_ascii(&file)
+        }
 
-            let header_args: usize;
-            match delim_vec.get(0) {
-                Some(&"P1") => header_args = 3,
-                Some(&"P2" | &"P3") => header_args = 4,
-                _ => return Err("Error: could not read file: magic number was not detected".to_string()),
+        /// Read given bytes to new image, detecting whether they hold the ascii or binary format
+        /// 
+        /// # Rules for arguments:
+        /// - Bytes must start with a PNM magic number (P1 to P6)
+        /// - Can take any PNM formatted file (PBM, PGM, PPM)
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let ascii = AnymapImage::read_from_bytes(b"P2\n2 1\n255\n0 255").unwrap();
+        /// let binary = AnymapImage::read_from_bytes(b"P5\n2 1\n255\n\x00\xff").unwrap();
+        /// assert_eq!(ascii.get_buffer(), binary.get_buffer());
+        /// ``` 
+        pub fn read_from_bytes(bytes: &[u8]) -> Result<AnymapImage, String> {
+            match bytes.get(..2) {
+                Some(b"P1" | b"P2" | b"P3") => {
+                    let text = std::str::from_utf8(bytes).map_err(|_| "Error: could not read file: ascii format holds non utf-8 bytes".to_string())?;
+                    parse_ascii(text)
+                },
+                Some(b"P4" | b"P5" | b"P6") => parse_binary(bytes),
+                _ => Err("Error: could not read file: valid magic number was not detected".to_string()),
             }
+        }
 
-            let mut parsed_header: Vec<usize> = Vec::new();
-            let mut header_args_count: usize = 1;
-            let mut idx = 1;
-            while header_args_count < header_args && idx < delim_vec.len() {
-                if delim_vec[idx].chars().all(|x| x.is_ascii_digit()) {
-                    let parsed_header_info = delim_vec[idx].parse::<usize>()
-                                                                  .map_err(|_| "Error: header holds non-standard arguments".to_string())?;
-                    header_args_count += 1;
-                    parsed_header.push(parsed_header_info);
-                }
-                idx += 1;
+        /// Read a whole image from any reader (ie. stdin, a socket or a file), detecting whether it holds the ascii or binary format
+        /// 
+        /// # Rules for arguments:
+        /// - Reader must hold a single image starting with a PNM magic number (P1 to P6)
+        /// - Can take any PNM formatted file (PBM, PGM, PPM)
+        /// 
+        /// # Example:
+        /// ```no_run
+        /// use ductr::AnymapImage;
+        /// 
+        /// // read an image piped through stdin
+        /// let image = AnymapImage::read_from(&mut std::io::stdin()).expect("Could not read stdin");
+        /// ``` 
+        pub fn read_from<R: Read>(reader: &mut R) -> Result<AnymapImage, String> {
+            let mut bytes = Vec::new();
+            if let Err(e) = reader.read_to_end(&mut bytes) {
+                return Err(format!("Error: could not read file: {:?}", e));
             }
+            AnymapImage::read_from_bytes(&bytes)
+        }
+    }
 
-            let mut byte_vector: Vec<u8> = Vec::new();
-            for byte in delim_vec[idx..].iter() {
-                let parsed_byte = byte.parse::<u8>()
-                                          .map_err(|_| "Error: byte array holds non-standard elements".to_string())?;
-                byte_vector.push(parsed_byte);
-            }
+    // Helper function parsing a binary format file (P4, P5, P6) into a new image
+    fn parse_binary(file: &[u8]) -> Result<AnymapImage, String> {
+        let magic_num = std::str::from_utf8(file.get(..2).unwrap_or_default()).map_err(|_| "Error: could not read file: magic number was not detected".to_string())?;
+        let header_args: usize = match magic_num {
+            "P4" => 3,
+            "P5" | "P6" => 4,
+            _ => return Err("Error: could not read file: valid magic number was not detected".to_string()),
+        };
+
+        let (header, byte_vector) = parse_header_to_slice(file, header_args);
+        if header.len() < header_args { return Err(format!("Error: incomplete header: expected {} fields, found {}", header_args, header.len())) }
+        let mut parsed_header: Vec<usize> = Vec::new();
+        for x in &header[1..] {
+            let parse_val = match x.parse::<usize>() {
+                Ok(parse_val) => parse_val,
+                Err(_) => return Err("The header format is not valid".to_string()), 
+            };
+            parsed_header.push(parse_val);
+        }
+
+        match magic_num {
+            "P4" => AnymapImage::pbm(realign_byte_buffer(byte_vector, parsed_header[0]), parsed_header[1], parsed_header[0]),
+            "P5" => AnymapImage::pgm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
+            "P6" => AnymapImage::ppm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
+            _ => unreachable!(),
+        }
+    }
 
-            let parsed_image: Result<AnymapImage, String>;
-            match delim_vec.get(0) {
-                Some(&"P1") => parsed_image = AnymapImage::pbm(byte_vector, parsed_header[1], parsed_header[0]),
-                Some(&"P2") => parsed_image = AnymapImage::pgm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
-                Some(&"P3") => parsed_image = AnymapImage::ppm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
-                _ => unreachable!(),
+    // Helper function parsing an ascii format file (P1, P2, P3) into a new image
+    fn parse_ascii(file: &str) -> Result<AnymapImage, String> {
+        let delim_vec = file.split_ascii_whitespace().collect::<Vec<&str>>();
+
+        let header_args: usize = match delim_vec.first() {
+            Some(&"P1") => 3,
+            Some(&"P2" | &"P3") => 4,
+            _ => return Err("Error: could not read file: magic number was not detected".to_string()),
+        };
+
+        let mut parsed_header: Vec<usize> = Vec::new();
+        let mut header_args_count: usize = 1;
+        let mut idx = 1;
+        while header_args_count < header_args && idx < delim_vec.len() {
+            if delim_vec[idx].chars().all(|x| x.is_ascii_digit()) {
+                let parsed_header_info = delim_vec[idx].parse::<usize>()
+                                                              .map_err(|_| "Error: header holds non-standard arguments".to_string())?;
+                header_args_count += 1;
+                parsed_header.push(parsed_header_info);
             }
-            parsed_image
+            idx += 1;
+        }
+        if header_args_count < header_args { return Err(format!("Error: incomplete header: expected {} fields, found {}", header_args, header_args_count)) }
+
+        let mut byte_vector: Vec<u8> = Vec::new();
+        for byte in delim_vec[idx..].iter() {
+            let parsed_byte = byte.parse::<u8>()
+                                      .map_err(|_| "Error: byte array holds non-standard elements".to_string())?;
+            byte_vector.push(parsed_byte);
+        }
+
+        match delim_vec.first() {
+            Some(&"P1") => AnymapImage::pbm(byte_vector, parsed_header[1], parsed_header[0]),
+            Some(&"P2") => AnymapImage::pgm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
+            Some(&"P3") => AnymapImage::ppm(byte_vector, parsed_header[2], parsed_header[1], parsed_header[0]),
+            _ => unreachable!(),
         }
     }
 
     /// Helper function for parsing header from binary file
     /// Returns a string vector containing header info, and a byte vector containing the buffer
-    fn parse_header_to_slice(byte_vec: &Vec<u8>, mut arg_count: usize) -> (Vec<&str>, Vec<u8>) {
+    fn parse_header_to_slice(byte_vec: &[u8], mut arg_count: usize) -> (Vec<&str>, Vec<u8>) {
         let mut start_idx = 0;
         let mut i = 0;
         let mut delim_vector: Vec<&str> = Vec::new();
         while i < byte_vec.len() {
-            if byte_vec[i] == '#' as u8 { // Eliminates comments from parser
-                while i < byte_vec.len() && byte_vec[i] != '\n' as u8 {
+            if byte_vec[i] == b'#' { // Eliminates comments from parser
+                while i < byte_vec.len() && byte_vec[i] != b'\n' {
                     i += 1;
                 }
                 i += 1;
                 start_idx = i;
                 continue;
             }
-            if byte_vec[i] == '\n' as u8 || byte_vec[i] == ' ' as u8 {
+            if byte_vec[i] == b'\n' || byte_vec[i] == b' ' {
                 match std::str::from_utf8(&byte_vec[start_idx..i]) {
                     Ok(header_data) => delim_vector.push(header_data.trim()),
                     Err(_) => break
@@ -280,7 +366,7 @@ pub mod io {
             }
             i += 1;
         }
-        (delim_vector, byte_vec[(start_idx+1)..].to_vec())
+        (delim_vector, byte_vec.get((start_idx+1)..).unwrap_or_default().to_vec())
     }
 
     // Helper function for parsing PBM misaligned bytes
@@ -288,17 +374,10 @@ pub mod io {
     // Returns new vector which is formatted to a byte per pixel
     fn realign_byte_buffer(byte_buffer: Vec<u8>, width: usize) -> Vec<u8> {
         let mut new_buffer: Vec<u8> = Vec::new();
-        let byte_alignment: (usize, usize) = ((width / 8) + 1, width % 8);
-        for (idx, byte) in byte_buffer.iter().enumerate() {
-            let mut bits_to_grab = 8;
-            if (idx +1) % byte_alignment.0 == 0 { bits_to_grab = byte_alignment.1 }
-            for idy in ((8 - bits_to_grab)..8).rev() {
-                if byte & (1 << idy) != 0 {
-                    new_buffer.push(1);
-                } else {
-                    new_buffer.push(0);
-                }
-            }
+        let bytes_per_row = width.div_ceil(8).max(1);   // every row is padded to a whole byte
+        for row in byte_buffer.chunks(bytes_per_row) {
+            let bits = row.iter().flat_map(|byte| (0..8).rev().map(move |idy| (byte >> idy) & 1));
+            new_buffer.extend(bits.take(width));
         }
         new_buffer
     }
@@ -50,8 +50,39 @@ pub struct AnymapImage {
     width: usize,
 }
 
+#[allow(clippy::module_inception)]
 mod io;
+#[allow(clippy::module_inception)]
 mod manipulation;
+mod filter;
+mod edge;
+mod morphology;
+mod threshold;
+mod dither;
+mod histogram;
+mod dump;
+mod colormap;
+mod font;
+mod compose;
+mod terminal;
+mod geometry;
+pub mod color;
+pub mod compare;
+pub mod testing;
+pub mod draw;
+pub mod plot;
+pub mod netpbm;
+pub mod pipeline;
+
+pub use edge::GradientOperator;
+pub use morphology::StructuringElement;
+pub use threshold::AdaptiveMethod;
+pub use dither::DitherMethod;
+pub use manipulation::manipulation::GreyscaleMethod;
+pub use dump::{debug_dump, debug_dump_as};
+pub use colormap::{ColorMap, ScalarRange};
+pub use compose::BlendMode;
+pub use terminal::AnsiMode;
 
 impl AnymapImage {
 
@@ -75,7 +106,7 @@ impl AnymapImage {
     pub fn pbm(buffer: Vec<u8>, height: usize, width: usize) -> Result<Self, String> {
         if buffer.len() != height*width { return Err(format!("Error: could not create PBM object: byte vector does not fit given dimensions: (buffer length: {}) != (given dimensions [h*w]: {})", buffer.len(), height*width)) }
         if buffer.iter()
-                    .any(|&b| b > 1)   { return Err(format!("Error: could not create PBM object: values must only consist of 1 or 0"))}
+                    .any(|&b| b > 1)   { return Err("Error: could not create PBM object: values must only consist of 1 or 0".to_string())}
         Ok(AnymapImage {
             buffer,
             magic_number: "pbm".to_string(),
@@ -197,4 +228,68 @@ impl AnymapImage {
     pub fn dimensions(&self) -> (usize, usize) {
         (self.height, self.width)
     }
+
+    // Helper function returning the amount of bytes used per pixel (ie. 1 for PBM & PGM, 3 for PPM)
+    pub(crate) fn channels(&self) -> usize {
+        if self.magic_number == "ppm" { 3 } else { 1 }
+    }
+
+    // Helper function returning the largest value a byte may hold (ie. 1 for PBM, saturation for PGM & PPM)
+    pub(crate) fn max_value(&self) -> usize {
+        if self.magic_number == "pbm" { 1 } else { self.saturation }
+    }
+
+    // Helper function splitting the buffer into one floating point plane per color channel
+    pub(crate) fn to_planes(&self) -> Vec<Vec<f64>> {
+        let channels = self.channels();
+        (0..channels).map(|c| self.buffer.iter()
+                                         .skip(c)
+                                         .step_by(channels)
+                                         .map(|&b| b as f64)
+                                         .collect())
+                     .collect()
+    }
+
+    // Helper function returning a single floating point luminance plane (Rec. 601 weights for PPM)
+    pub(crate) fn luminance_plane(&self) -> Vec<f64> {
+        match self.magic_number.as_str() {
+            "ppm" => self.buffer.chunks(3)
+                                .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+                                .collect(),
+            _ => self.buffer.iter().map(|&b| b as f64).collect(),
+        }
+    }
+
+    // Helper function returning the 8 bit luminance of every pixel (PBM black pixels become 0)
+    pub(crate) fn greyscale_base(&self) -> Vec<u8> {
+        let max = self.max_value() as f64;
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+        match self.magic_number.as_str() {
+            "pbm" => self.buffer.iter().map(|&b| if b == 1 { 0 } else { 255 }).collect(),
+            _ => self.luminance_plane().iter().map(|v| (v * scale).round().clamp(0.0, 255.0) as u8).collect(),
+        }
+    }
+
+    // Helper function returning the 8 bit RGB color of every pixel (PBM black pixels become 0, PGM values are repeated)
+    pub(crate) fn rgb_base(&self) -> Vec<u8> {
+        let max = self.max_value() as f64;
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+        match self.magic_number.as_str() {
+            "pbm" => self.buffer.iter().flat_map(|&b| [if b == 1 { 0 } else { 255 }; 3]).collect(),
+            "pgm" => self.buffer.iter().flat_map(|&b| [(b as f64 * scale).round().min(255.0) as u8; 3]).collect(),
+            _ => self.buffer.iter().map(|&b| (b as f64 * scale).round().min(255.0) as u8).collect(),
+        }
+    }
+
+    // Helper function writing floating point planes back into the buffer
+    // Values are rounded and clamped to the range of `0..=saturation`
+    pub(crate) fn set_planes(&mut self, planes: &[Vec<f64>]) {
+        let channels = self.channels();
+        let max = self.max_value() as f64;
+        for (c, plane) in planes.iter().enumerate() {
+            for (idx, value) in plane.iter().enumerate() {
+                self.buffer[idx*channels + c] = value.round().clamp(0.0, max) as u8;
+            }
+        }
+    }
 }    
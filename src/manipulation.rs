@@ -1,12 +1,56 @@
 pub mod manipulation {
 
     use crate::AnymapImage;
+    use crate::color::{linear_to_rgb, rgb_to_lab, rgb_to_linear};
+
+    /// Methods used to reduce a color pixel to a single grey value
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+    pub enum GreyscaleMethod {
+        /// unweighted mean of the three channels
+        Average,
+        /// Rec. 601 luma weights (0.299, 0.587, 0.114) applied to the gamma encoded values
+        Rec601,
+        /// Rec. 709 luma weights (0.2126, 0.7152, 0.0722) applied to the gamma encoded values
+        Rec709,
+        /// Rec. 709 weights applied in linear light, then encoded back with the sRGB curve
+        LinearLuminance,
+        /// CIE L* perceptual lightness
+        Lightness,
+        /// mean of the largest and smallest channel (HSL lightness)
+        Desaturate,
+        /// copies a single channel (0 for red, 1 for green, 2 for blue)
+        Channel(usize),
+    }
+
+    // Helper function reducing a single RGB pixel to a grey value with the given method
+    fn grey_value(pixel: &[u8], saturation: usize, method: GreyscaleMethod) -> u8 {
+        let max = saturation as f64;
+        if max == 0.0 { return 0 }
+        let rgb = [pixel[0] as f64 / max, pixel[1] as f64 / max, pixel[2] as f64 / max];
+        let grey = match method {
+            GreyscaleMethod::Average => return ((pixel[0] as usize + pixel[1] as usize + pixel[2] as usize) / 3) as u8,
+            GreyscaleMethod::Rec601 => 0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2],
+            GreyscaleMethod::Rec709 => 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2],
+            GreyscaleMethod::LinearLuminance => {
+                let linear = rgb_to_linear(rgb);
+                linear_to_rgb([0.2126 * linear[0] + 0.7152 * linear[1] + 0.0722 * linear[2]; 3])[0]
+            },
+            GreyscaleMethod::Lightness => rgb_to_lab(rgb)[0] / 100.0,
+            GreyscaleMethod::Desaturate => (rgb[0].max(rgb[1]).max(rgb[2]) + rgb[0].min(rgb[1]).min(rgb[2])) / 2.0,
+            GreyscaleMethod::Channel(channel) => return pixel[channel],
+        };
+        (grey * max).round().clamp(0.0, max) as u8
+    }
 
     #[allow(dead_code)]
     impl AnymapImage {
 
         /// Inverts AnymapImage struct into a suitable inverted image
         /// 
+        /// # Notes
+        /// - PBM pixels swap between black and white, PGM and PPM values become `saturation - value`
+        /// 
         /// # Example:
         /// ```
         /// use ductr::AnymapImage;
@@ -22,7 +66,7 @@ pub mod manipulation {
         pub fn invert(&mut self) {
             match self.magic_number.as_str() {
                 "pbm" => self.buffer = self.buffer.iter().map(|x| 1 - x).collect::<Vec<u8>>(),
-                _ => self.buffer = self.buffer.iter().map(|x| !x).collect::<Vec<u8>>()
+                _ => self.buffer = self.buffer.iter().map(|x| (self.saturation as u8).saturating_sub(*x)).collect::<Vec<u8>>()
             }
         }
 
@@ -64,6 +108,9 @@ pub mod manipulation {
         /// # Rules for arguments:
         /// - while it will take any AnymapImage format without error, it only performs greyscaling on PPM color images
         /// 
+        /// # Notes
+        /// - uses the unweighted average of the three channels (see `greyscale_with` for other methods)
+        /// 
         /// # Example:
         /// ```
         /// use ductr::AnymapImage;
@@ -85,20 +132,316 @@ pub mod manipulation {
         /// ppm_red_greyscale.greyscale();
         /// ``` 
         pub fn greyscale(&mut self) {
+            // the average method never fails, so the result can be ignored
+            let _ = self.greyscale_with(GreyscaleMethod::Average);
+        }
+
+        /// Greyscales all pixels in the AnymapImage struct with the given method, keeping the PPM format
+        /// 
+        /// # Rules for arguments:
+        /// - `GreyscaleMethod::Channel` must index a channel in the range of `0..3`
+        /// - while it will take any AnymapImage format without error, it only performs greyscaling on PPM color images
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::{AnymapImage, GreyscaleMethod};
+        /// 
+        /// // create red 1x1 PPM AnymapImage object
+        /// let mut ppm = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+        /// 
+        /// // convert with Rec. 709 luma weights
+        /// ppm.greyscale_with(GreyscaleMethod::Rec709).unwrap();
+        /// assert_eq!(ppm.get_buffer(), vec![54, 54, 54]);
+        /// ``` 
+        pub fn greyscale_with(&mut self, method: GreyscaleMethod) -> Result<(), String> {
+            if let GreyscaleMethod::Channel(channel) = method {
+                if channel > 2 { return Err(format!("Error: channel index must fall in the range of 0..3 ({})", channel)) }
+            }
+            if self.magic_number != "ppm" { return Ok(()) }
+
+            let max = self.saturation;
+            for pixel in self.buffer.chunks_mut(3) {
+                let grey = grey_value(pixel, max, method);
+                pixel.fill(grey);
+            }
+            Ok(())
+        }
+
+        /// Converts the AnymapImage struct into a PGM image using the given greyscale method
+        /// 
+        /// # Rules for arguments:
+        /// - `GreyscaleMethod::Channel` must index a channel in the range of `0..3`
+        /// 
+        /// # Notes
+        /// - PGM images are returned unchanged, whatever the method
+        /// - PBM images become a PGM with a saturation of 1 (black pixels become 0, white pixels become 1)
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::{AnymapImage, GreyscaleMethod};
+        /// 
+        /// // extract the green channel of a 1x2 PPM AnymapImage object
+        /// let ppm = AnymapImage::ppm(vec![10, 20, 30, 40, 50, 60], 255, 1, 2).unwrap();
+        /// let pgm = ppm.to_pgm(GreyscaleMethod::Channel(1)).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![20, 50]);
+        /// ``` 
+        pub fn to_pgm(&self, method: GreyscaleMethod) -> Result<AnymapImage, String> {
+            if let GreyscaleMethod::Channel(channel) = method {
+                if channel > 2 { return Err(format!("Error: channel index must fall in the range of 0..3 ({})", channel)) }
+            }
+
             match self.magic_number.as_str() {
-                "ppm" => {
-                    for idx in (0..(self.buffer.len()-3)).step_by(3) {
-                        let greyscale_pixel: usize = (self.buffer[idx] as usize + 
-                                                     self.buffer[idx+1] as usize +
-                                                     self.buffer[idx+2] as usize) / 3;
-
-                        self.buffer[idx]    = greyscale_pixel as u8;
-                        self.buffer[idx+1]  = greyscale_pixel as u8;
-                        self.buffer[idx+2]  = greyscale_pixel as u8;
-                    }
-                },
-                _ => ()
+                "pbm" => AnymapImage::pgm(self.buffer.iter().map(|x| 1 - x).collect(), 1, self.height, self.width),
+                "ppm" => AnymapImage::pgm(self.buffer.chunks(3).map(|pixel| grey_value(pixel, self.saturation, method)).collect(), self.saturation, self.height, self.width),
+                _ => Ok(self.clone()),
+            }
+        }
+
+        /// Splits a PPM AnymapImage struct into three PGM images holding its red, green and blue channels
+        /// 
+        /// # Rules for arguments:
+        /// - only PPM formats are accepted
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let ppm = AnymapImage::ppm(vec![10, 20, 30, 40, 50, 60], 255, 1, 2).unwrap();
+        /// let [red, green, blue] = ppm.split_channels().unwrap();
+        /// assert_eq!(red.get_buffer(), vec![10, 40]);
+        /// assert_eq!(blue.get_buffer(), vec![30, 60]);
+        /// ``` 
+        pub fn split_channels(&self) -> Result<[AnymapImage; 3], String> {
+            if self.magic_number != "ppm" { return Err("Error: only ppm images can be split into channels".to_string()) }
+
+            let channel = |c: usize| AnymapImage::pgm(self.buffer.iter().skip(c).step_by(3).copied().collect(), self.saturation, self.height, self.width);
+            Ok([channel(0)?, channel(1)?, channel(2)?])
+        }
+
+        /// Merges three PGM images into a single PPM image, using them as the red, green and blue channels
+        /// 
+        /// # Rules for arguments:
+        /// - all three images must be PGM formats
+        /// - all three images must share the same dimensions and saturation
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let red = AnymapImage::pgm(vec![10, 40], 255, 1, 2).unwrap();
+        /// let green = AnymapImage::pgm(vec![20, 50], 255, 1, 2).unwrap();
+        /// let blue = AnymapImage::pgm(vec![30, 60], 255, 1, 2).unwrap();
+        /// 
+        /// let ppm = AnymapImage::merge_channels(&red, &green, &blue).unwrap();
+        /// assert_eq!(ppm.get_buffer(), vec![10, 20, 30, 40, 50, 60]);
+        /// ``` 
+        pub fn merge_channels(red: &AnymapImage, green: &AnymapImage, blue: &AnymapImage) -> Result<AnymapImage, String> {
+            let channels = [red, green, blue];
+            if channels.iter().any(|c| c.magic_number != "pgm") { return Err("Error: only pgm images can be merged into channels".to_string()) }
+            if channels.iter().any(|c| c.dimensions() != red.dimensions()) { return Err("Error: channel images must share the same dimensions".to_string()) }
+            if channels.iter().any(|c| c.saturation != red.saturation) { return Err("Error: channel images must share the same saturation".to_string()) }
+
+            let buffer = (0..red.buffer.len()).flat_map(|idx| channels.map(|c| c.buffer[idx]))
+                                              .collect();
+            AnymapImage::ppm(buffer, red.saturation, red.height, red.width)
+        }
+
+        /// Reorders the color channels of a PPM AnymapImage struct
+        /// 
+        /// # Rules for arguments:
+        /// - `order` must hold exactly three of the letters `r`, `g` and `b` (case insensitive), naming the source of each output channel
+        /// - letters may repeat (ie. `"rrr"` copies the red channel everywhere)
+        /// - only PPM formats are accepted
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// // fix a buffer that was stored in BGR order
+        /// let mut ppm = AnymapImage::ppm(vec![30, 20, 10], 255, 1, 1).unwrap();
+        /// ppm.swizzle("bgr").unwrap();
+        /// assert_eq!(ppm.get_buffer(), vec![10, 20, 30]);
+        /// ``` 
+        pub fn swizzle(&mut self, order: &str) -> Result<(), String> {
+            if self.magic_number != "ppm" { return Err("Error: only ppm images can be swizzled".to_string()) }
+
+            let sources = order.chars()
+                               .map(|c| match c.to_ascii_lowercase() {
+                                   'r' => Ok(0),
+                                   'g' => Ok(1),
+                                   'b' => Ok(2),
+                                   _ => Err(format!("Error: swizzle order holds an unknown channel ({})", c)),
+                               })
+                               .collect::<Result<Vec<usize>, String>>()?;
+            if sources.len() != 3 { return Err(format!("Error: swizzle order must name exactly 3 channels ({})", order)) }
+
+            for pixel in self.buffer.chunks_mut(3) {
+                let original = [pixel[0], pixel[1], pixel[2]];
+                for (c, &source) in sources.iter().enumerate() {
+                    pixel[c] = original[source];
+                }
             }
+            Ok(())
+        }
+
+        /// Applies a lookup table to every color channel of the AnymapImage struct
+        /// 
+        /// # Rules for arguments:
+        /// - `lut` must hold `saturation + 1` entries, one for every possible value
+        /// - entries of `lut` must be lesser than or equal to the saturation
+        /// - PBM formats are not accepted
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// // swap the two lowest values of a 2 bit PGM
+        /// let mut pgm = AnymapImage::pgm(vec![0, 1, 2, 3], 3, 2, 2).unwrap();
+        /// pgm.apply_lookup_table(&[1, 0, 2, 3]).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![1, 0, 2, 3]);
+        /// ``` 
+        pub fn apply_lookup_table(&mut self, lut: &[u8]) -> Result<(), String> {
+            if self.magic_number == "pbm" { return Err("Error: pbm images cannot have tones adjusted".to_string()) }
+            if lut.len() != self.saturation + 1 { return Err(format!("Error: lookup table must hold saturation + 1 entries: (table length: {}) != (saturation + 1: {})", lut.len(), self.saturation + 1)) }
+            if lut.iter().any(|&v| v as usize > self.saturation) { return Err(format!("Error: lookup table holds values greater than the saturation ({})", self.saturation)) }
+
+            for byte in self.buffer.iter_mut() {
+                *byte = lut[(*byte as usize).min(self.saturation)];
+            }
+            Ok(())
+        }
+
+        /// Applies a gamma curve to every color channel of the AnymapImage struct
+        /// 
+        /// # Rules for arguments:
+        /// - `gamma` must be greater than 0
+        /// - PBM formats are not accepted
+        /// 
+        /// # Notes
+        /// - values are raised to the power of `1 / gamma`, so a gamma above 1 brightens midtones and a gamma below 1 darkens them
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let mut pgm = AnymapImage::pgm(vec![0, 64, 255], 255, 1, 3).unwrap();
+        /// pgm.adjust_gamma(2.0).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![0, 128, 255]);
+        /// ``` 
+        pub fn adjust_gamma(&mut self, gamma: f64) -> Result<(), String> {
+            if gamma.is_nan() || gamma <= 0.0 { return Err(format!("Error: gamma must be greater than 0 ({})", gamma)) }
+            let max = self.saturation as f64;
+            let lut = self.tone_lookup_table(|v| max * (v / max).powf(1.0 / gamma));
+            self.apply_lookup_table(&lut)
+        }
+
+        /// Adjusts the brightness and contrast of every color channel of the AnymapImage struct
+        /// 
+        /// # Rules for arguments:
+        /// - `brightness` is added to every value (ie. 20.0 lightens an 8 bit image by 20 levels)
+        /// - `contrast` scales the distance of every value from mid grey (ie. 1.0 leaves the image unchanged, 0.0 turns it flat grey) and must not be negative
+        /// - PBM formats are not accepted
+        /// 
+        /// # Notes
+        /// - results are clamped to the range of `0..=saturation`
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// let mut pgm = AnymapImage::pgm(vec![100, 150], 255, 1, 2).unwrap();
+        /// pgm.brightness_contrast(10.0, 2.0).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![83, 183]);
+        /// ``` 
+        pub fn brightness_contrast(&mut self, brightness: f64, contrast: f64) -> Result<(), String> {
+            if contrast.is_nan() || contrast < 0.0 { return Err(format!("Error: contrast must not be negative ({})", contrast)) }
+            if brightness.is_nan() { return Err("Error: brightness must be a number".to_string()) }
+            let mid = self.saturation as f64 / 2.0;
+            let lut = self.tone_lookup_table(|v| (v - mid) * contrast + mid + brightness);
+            self.apply_lookup_table(&lut)
+        }
+
+        /// Remaps every color channel of the AnymapImage struct from an input range to an output range with a midtone gamma
+        /// 
+        /// # Rules for arguments:
+        /// - `in_black` must be lesser than `in_white`, and both must be lesser than or equal to the saturation
+        /// - `out_black` and `out_white` must be lesser than or equal to the saturation (`out_black` may exceed `out_white` to invert the image)
+        /// - `gamma` must be greater than 0 (values above 1 brighten midtones)
+        /// - PBM formats are not accepted
+        /// 
+        /// # Notes
+        /// - values at or below `in_black` map to `out_black`, values at or above `in_white` map to `out_white`
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// // stretch the range 50..=150 over the full 8 bit range
+        /// let mut pgm = AnymapImage::pgm(vec![0, 50, 100, 150, 255], 255, 1, 5).unwrap();
+        /// pgm.levels(50, 150, 1.0, 0, 255).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![0, 0, 128, 255, 255]);
+        /// ``` 
+        pub fn levels(&mut self, in_black: usize, in_white: usize, gamma: f64, out_black: usize, out_white: usize) -> Result<(), String> {
+            if in_black >= in_white { return Err(format!("Error: input black point must be lesser than input white point ({} >= {})", in_black, in_white)) }
+            if [in_black, in_white, out_black, out_white].iter().any(|&v| v > self.saturation) { return Err(format!("Error: levels must not exceed the saturation ({})", self.saturation)) }
+            if gamma.is_nan() || gamma <= 0.0 { return Err(format!("Error: gamma must be greater than 0 ({})", gamma)) }
+
+            let (in_black, in_white) = (in_black as f64, in_white as f64);
+            let (out_black, out_white) = (out_black as f64, out_white as f64);
+            let lut = self.tone_lookup_table(|v| {
+                let normalized = ((v.clamp(in_black, in_white) - in_black) / (in_white - in_black)).powf(1.0 / gamma);
+                out_black + (out_white - out_black) * normalized
+            });
+            self.apply_lookup_table(&lut)
+        }
+
+        /// Applies a piecewise linear tone curve to every color channel of the AnymapImage struct
+        /// 
+        /// # Rules for arguments:
+        /// - `points` holds (input, output) control points, which must all be lesser than or equal to the saturation
+        /// - `points` must hold at least one point and no two points may share the same input
+        /// - PBM formats are not accepted
+        /// 
+        /// # Notes
+        /// - points do not need to be sorted
+        /// - inputs before the first point or after the last point keep the output of that point
+        /// 
+        /// # Example:
+        /// ```
+        /// use ductr::AnymapImage;
+        /// 
+        /// // boost shadows while leaving black and white alone
+        /// let mut pgm = AnymapImage::pgm(vec![0, 32, 64, 160, 255], 255, 1, 5).unwrap();
+        /// pgm.curves(&[(0, 0), (64, 128), (255, 255)]).unwrap();
+        /// assert_eq!(pgm.get_buffer(), vec![0, 64, 128, 192, 255]);
+        /// ``` 
+        pub fn curves(&mut self, points: &[(usize, usize)]) -> Result<(), String> {
+            if points.is_empty() { return Err("Error: curves need at least one control point".to_string()) }
+            if points.iter().any(|&(x, y)| x > self.saturation || y > self.saturation) { return Err(format!("Error: control points must not exceed the saturation ({})", self.saturation)) }
+
+            let mut points = points.to_vec();
+            points.sort();
+            if points.windows(2).any(|p| p[0].0 == p[1].0) { return Err("Error: control points must not share the same input".to_string()) }
+
+            let lut = self.tone_lookup_table(|v| {
+                let next = points.iter().position(|&(x, _)| x as f64 >= v);
+                match next {
+                    Some(0) => points[0].1 as f64,
+                    None => points[points.len() - 1].1 as f64,
+                    Some(idx) => {
+                        let ((x0, y0), (x1, y1)) = (points[idx - 1], points[idx]);
+                        y0 as f64 + (y1 as f64 - y0 as f64) * (v - x0 as f64) / (x1 - x0) as f64
+                    }
+                }
+            });
+            self.apply_lookup_table(&lut)
+        }
+
+        // Helper function building a lookup table from a tone function, rounding and clamping its results to `0..=saturation`
+        fn tone_lookup_table<F>(&self, tone: F) -> Vec<u8>
+        where F: Fn(f64) -> f64 {
+            let max = self.saturation as f64;
+            (0..=self.saturation).map(|v| tone(v as f64).round().clamp(0.0, max) as u8).collect()
         }
     }
 }
\ No newline at end of file
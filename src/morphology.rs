@@ -0,0 +1,447 @@
+use crate::AnymapImage;
+
+/// Flat structuring element used by the morphology operations of `AnymapImage`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuringElement {
+    mask: Vec<u8>,
+    height: usize,
+    width: usize,
+    origin: (usize, usize),
+}
+
+impl StructuringElement {
+
+    /// # Constructor for a custom structuring element
+    ///
+    /// # Rules for arguments:
+    /// - length of `mask` must equal the product of the width and height inputs
+    /// - `mask` must consist only of bytes equal to 0 or 1, with at least one 1
+    /// - `origin` is given as (row, column) and must fall inside the mask
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::StructuringElement;
+    ///
+    /// // horizontal line of three pixels, anchored on its left end
+    /// let line = StructuringElement::new(vec![1, 1, 1], 1, 3, (0, 0)).unwrap();
+    /// ```
+    pub fn new(mask: Vec<u8>, height: usize, width: usize, origin: (usize, usize)) -> Result<Self, String> {
+        if mask.len() != height*width { return Err(format!("Error: could not create structuring element: mask does not fit given dimensions: (mask length: {}) != (given dimensions [h*w]: {})", mask.len(), height*width)) }
+        if mask.iter().any(|&b| b > 1) { return Err("Error: could not create structuring element: values must only consist of 1 or 0".to_string()) }
+        if mask.iter().all(|&b| b == 0) { return Err("Error: could not create structuring element: mask is empty".to_string()) }
+        if origin.0 >= height || origin.1 >= width { return Err(format!("Error: could not create structuring element: origin {:?} lies outside of the mask", origin)) }
+
+        Ok(StructuringElement { mask, height, width, origin })
+    }
+
+    /// Creates a filled `(2*radius+1)` square structuring element centered on its middle pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::StructuringElement;
+    ///
+    /// let square = StructuringElement::square(1);
+    /// assert_eq!(square.dimensions(), (3, 3));
+    /// ```
+    pub fn square(radius: usize) -> Self {
+        let size = 2*radius + 1;
+        StructuringElement { mask: vec![1; size*size], height: size, width: size, origin: (radius, radius) }
+    }
+
+    /// Creates a filled rectangle structuring element centered on its middle pixel (rounded towards the top left)
+    ///
+    /// # Rules for arguments:
+    /// - `height` and `width` must both be greater than 0
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::StructuringElement;
+    ///
+    /// let rectangle = StructuringElement::rectangle(1, 5).unwrap();
+    /// assert_eq!(rectangle.dimensions(), (1, 5));
+    /// ```
+    pub fn rectangle(height: usize, width: usize) -> Result<Self, String> {
+        StructuringElement::new(vec![1; height*width], height, width, (height.saturating_sub(1) / 2, width.saturating_sub(1) / 2))
+    }
+
+    /// Creates a `(2*radius+1)` plus shaped structuring element centered on its middle pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::StructuringElement;
+    ///
+    /// let cross = StructuringElement::cross(1);
+    /// assert_eq!(cross.get_mask(), vec![0, 1, 0,
+    ///                                   1, 1, 1,
+    ///                                   0, 1, 0]);
+    /// ```
+    pub fn cross(radius: usize) -> Self {
+        let size = 2*radius + 1;
+        let mask = (0..size*size).map(|idx| (idx / size == radius || idx % size == radius) as u8).collect();
+        StructuringElement { mask, height: size, width: size, origin: (radius, radius) }
+    }
+
+    /// Creates a `(2*radius+1)` disk shaped structuring element centered on its middle pixel
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::StructuringElement;
+    ///
+    /// let disk = StructuringElement::disk(2);
+    /// assert_eq!(disk.get_mask().iter().filter(|&&b| b == 1).count(), 13);
+    /// ```
+    pub fn disk(radius: usize) -> Self {
+        let size = 2*radius + 1;
+        let r = radius as isize;
+        let mask = (0..size*size).map(|idx| {
+                                     let (dy, dx) = ((idx / size) as isize - r, (idx % size) as isize - r);
+                                     (dy*dy + dx*dx <= r*r) as u8
+                                 })
+                                 .collect();
+        StructuringElement { mask, height: size, width: size, origin: (radius, radius) }
+    }
+
+    /// Clones and returns the byte vector `mask` of the structuring element
+    pub fn get_mask(&self) -> Vec<u8> {
+        self.mask.clone()
+    }
+
+    /// returns dimensions as (height, width) tuple
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    // Helper function returning the (row, column) offsets of every set pixel relative to the origin
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        self.mask.iter()
+                 .enumerate()
+                 .filter(|(_, &b)| b == 1)
+                 .map(|(idx, _)| ((idx / self.width) as isize - self.origin.0 as isize,
+                                  (idx % self.width) as isize - self.origin.1 as isize))
+                 .collect()
+    }
+}
+
+impl AnymapImage {
+
+    /// Erodes the AnymapImage struct with the given structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM (binary morphology) and PGM (greyscale morphology) formats are accepted
+    ///
+    /// # Notes
+    /// - PBM foreground is the black (1) pixels, so erosion shrinks black shapes
+    /// - pixels outside of the image are ignored
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // create 3x3 PBM AnymapImage object holding a single black pixel
+    /// let mut pbm = AnymapImage::pbm(vec![0, 0, 0, 0, 1, 0, 0, 0, 0], 3, 3).unwrap();
+    ///
+    /// // a lone pixel does not survive a 3x3 erosion
+    /// pbm.erode(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0; 9]);
+    /// ```
+    pub fn erode(&mut self, element: &StructuringElement) -> Result<(), String> {
+        self.check_morphology_format()?;
+        self.buffer = erode_plane(&self.buffer, self.width, self.height, element);
+        Ok(())
+    }
+
+    /// Dilates the AnymapImage struct with the given structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM (binary morphology) and PGM (greyscale morphology) formats are accepted
+    ///
+    /// # Notes
+    /// - PBM foreground is the black (1) pixels, so dilation grows black shapes
+    /// - pixels outside of the image are ignored
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // create 3x3 PBM AnymapImage object holding a single black pixel
+    /// let mut pbm = AnymapImage::pbm(vec![0, 0, 0, 0, 1, 0, 0, 0, 0], 3, 3).unwrap();
+    ///
+    /// pbm.dilate(&StructuringElement::cross(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 1, 0, 1, 1, 1, 0, 1, 0]);
+    /// ```
+    pub fn dilate(&mut self, element: &StructuringElement) -> Result<(), String> {
+        self.check_morphology_format()?;
+        self.buffer = dilate_plane(&self.buffer, self.width, self.height, element);
+        Ok(())
+    }
+
+    /// Opens the AnymapImage struct (erosion followed by dilation), removing foreground details smaller than the structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM and PGM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![0, 0, 0, 0, 1, 0, 0, 0, 0], 3, 3).unwrap();
+    /// pbm.open(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0; 9]);
+    /// ```
+    pub fn open(&mut self, element: &StructuringElement) -> Result<(), String> {
+        self.erode(element)?;
+        self.dilate(element)
+    }
+
+    /// Closes the AnymapImage struct (dilation followed by erosion), filling background gaps smaller than the structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM and PGM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![1, 1, 1, 1, 0, 1, 1, 1, 1], 3, 3).unwrap();
+    /// pbm.close(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1; 9]);
+    /// ```
+    pub fn close(&mut self, element: &StructuringElement) -> Result<(), String> {
+        self.dilate(element)?;
+        self.erode(element)
+    }
+
+    /// Replaces the AnymapImage struct with its morphological gradient (dilation minus erosion)
+    ///
+    /// # Rules for arguments:
+    /// - only PBM and PGM formats are accepted
+    ///
+    /// # Notes
+    /// - for PBM images this keeps the pixels lying on the boundary of each shape
+    /// - differences are clamped at 0, which only matters for structuring elements leaving their origin unset
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// let mut pgm = AnymapImage::pgm(vec![10, 10, 50, 50], 255, 1, 4).unwrap();
+    /// pgm.morphological_gradient(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 40, 40, 0]);
+    /// ```
+    pub fn morphological_gradient(&mut self, element: &StructuringElement) -> Result<(), String> {
+        self.check_morphology_format()?;
+        let eroded = erode_plane(&self.buffer, self.width, self.height, element);
+        let dilated = dilate_plane(&self.buffer, self.width, self.height, element);
+        self.buffer = dilated.iter().zip(eroded.iter()).map(|(d, e)| d.saturating_sub(*e)).collect();
+        Ok(())
+    }
+
+    /// Replaces the AnymapImage struct with its white top-hat (image minus its opening), keeping foreground details smaller than the structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM and PGM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // bright spike on a dark plateau
+    /// let mut pgm = AnymapImage::pgm(vec![20, 20, 90, 20, 20], 255, 1, 5).unwrap();
+    /// pgm.top_hat(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 0, 70, 0, 0]);
+    /// ```
+    pub fn top_hat(&mut self, element: &StructuringElement) -> Result<(), String> {
+        let original = self.buffer.clone();
+        self.open(element)?;
+        self.buffer = original.iter().zip(self.buffer.iter()).map(|(a, o)| a.saturating_sub(*o)).collect();
+        Ok(())
+    }
+
+    /// Replaces the AnymapImage struct with its black top-hat (closing minus image), keeping background details smaller than the structuring element
+    ///
+    /// # Rules for arguments:
+    /// - only PBM and PGM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // dark pit in a bright plateau
+    /// let mut pgm = AnymapImage::pgm(vec![90, 90, 20, 90, 90], 255, 1, 5).unwrap();
+    /// pgm.black_hat(&StructuringElement::square(1)).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![0, 0, 70, 0, 0]);
+    /// ```
+    pub fn black_hat(&mut self, element: &StructuringElement) -> Result<(), String> {
+        let original = self.buffer.clone();
+        self.close(element)?;
+        self.buffer = self.buffer.iter().zip(original.iter()).map(|(c, a)| c.saturating_sub(*a)).collect();
+        Ok(())
+    }
+
+    /// Applies the hit-or-miss transform to the AnymapImage struct
+    /// Keeps only the pixels where every `hit` pixel lands on foreground and every `miss` pixel lands on background
+    ///
+    /// # Rules for arguments:
+    /// - only PBM formats are accepted
+    ///
+    /// # Notes
+    /// - pixels outside of the image count as background
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // find isolated pixels
+    /// let hit = StructuringElement::new(vec![0, 0, 0, 0, 1, 0, 0, 0, 0], 3, 3, (1, 1)).unwrap();
+    /// let miss = StructuringElement::new(vec![1, 1, 1, 1, 0, 1, 1, 1, 1], 3, 3, (1, 1)).unwrap();
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![1, 0, 0, 0,
+    ///                                     0, 0, 1, 1], 2, 4).unwrap();
+    /// pbm.hit_or_miss(&hit, &miss).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 0, 0, 0,
+    ///                                   0, 0, 0, 0]);
+    /// ```
+    pub fn hit_or_miss(&mut self, hit: &StructuringElement, miss: &StructuringElement) -> Result<(), String> {
+        if self.magic_number != "pbm" { return Err("Error: hit-or-miss transform is only defined for pbm images".to_string()) }
+        self.buffer = hit_or_miss_plane(&self.buffer, self.width, self.height, hit, miss);
+        Ok(())
+    }
+
+    /// Thins the AnymapImage struct once, removing every pixel matched by the hit-or-miss transform of `hit` and `miss`
+    ///
+    /// # Rules for arguments:
+    /// - only PBM formats are accepted
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, StructuringElement};
+    ///
+    /// // remove right end points of horizontal lines
+    /// let hit = StructuringElement::new(vec![1, 1], 1, 2, (0, 1)).unwrap();
+    /// let miss = StructuringElement::new(vec![0, 0, 1], 1, 3, (0, 1)).unwrap();
+    ///
+    /// let mut pbm = AnymapImage::pbm(vec![1, 1, 1, 0], 1, 4).unwrap();
+    /// pbm.thin(&hit, &miss).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 0, 0]);
+    /// ```
+    pub fn thin(&mut self, hit: &StructuringElement, miss: &StructuringElement) -> Result<(), String> {
+        if self.magic_number != "pbm" { return Err("Error: thinning is only defined for pbm images".to_string()) }
+        let matched = hit_or_miss_plane(&self.buffer, self.width, self.height, hit, miss);
+        self.buffer = self.buffer.iter().zip(matched.iter()).map(|(a, m)| a.saturating_sub(*m)).collect();
+        Ok(())
+    }
+
+    /// Reduces every shape of the AnymapImage struct to a one pixel wide skeleton
+    ///
+    /// # Rules for arguments:
+    /// - only PBM formats are accepted
+    ///
+    /// # Notes
+    /// - repeatedly thins the image with the eight rotations of the Golay L elements until it stops changing
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // create 5x7 PBM AnymapImage object holding a filled 3x5 rectangle
+    /// let mut buffer = vec![0; 5*7];
+    /// for y in 1..4 { for x in 1..6 { buffer[y*7 + x] = 1; } }
+    /// let mut pbm = AnymapImage::pbm(buffer, 5, 7).unwrap();
+    ///
+    /// pbm.skeletonize().unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![0, 0, 0, 0, 0, 0, 0,
+    ///                                   0, 1, 0, 0, 0, 1, 0,
+    ///                                   0, 1, 1, 1, 1, 1, 0,
+    ///                                   0, 1, 0, 0, 0, 1, 0,
+    ///                                   0, 0, 0, 0, 0, 0, 0]);
+    /// ```
+    pub fn skeletonize(&mut self) -> Result<(), String> {
+        if self.magic_number != "pbm" { return Err("Error: skeletonization is only defined for pbm images".to_string()) }
+
+        let mut elements = Vec::new();
+        for (hit, miss) in [([0, 0, 0, 0, 1, 0, 1, 1, 1], [1, 1, 1, 0, 0, 0, 0, 0, 0]),
+                            ([0, 0, 0, 1, 1, 0, 0, 1, 0], [0, 1, 1, 0, 0, 1, 0, 0, 0])] {
+            let (mut hit, mut miss) = (hit, miss);
+            for _ in 0..4 {
+                elements.push((StructuringElement::new(hit.to_vec(), 3, 3, (1, 1))?,
+                               StructuringElement::new(miss.to_vec(), 3, 3, (1, 1))?));
+                hit = rotate_3x3(hit);
+                miss = rotate_3x3(miss);
+            }
+        }
+
+        loop {
+            let previous = self.buffer.clone();
+            for (hit, miss) in elements.iter() {
+                self.thin(hit, miss)?;
+            }
+            if previous == self.buffer { break; }
+        }
+        Ok(())
+    }
+
+    // Helper function rejecting formats that morphology is not defined for
+    fn check_morphology_format(&self) -> Result<(), String> {
+        if self.magic_number == "ppm" { return Err("Error: morphology is only defined for pbm and pgm images".to_string()) }
+        Ok(())
+    }
+}
+
+// Helper function rotating a row-major 3x3 mask by 90 degrees clockwise
+fn rotate_3x3(mask: [u8; 9]) -> [u8; 9] {
+    let mut rotated = [0; 9];
+    for y in 0..3 {
+        for x in 0..3 {
+            rotated[x*3 + (2 - y)] = mask[y*3 + x];
+        }
+    }
+    rotated
+}
+
+// Helper function taking the minimum under the structuring element at every pixel, ignoring pixels outside of the plane
+fn erode_plane(plane: &[u8], width: usize, height: usize, element: &StructuringElement) -> Vec<u8> {
+    let offsets = element.offsets();
+    neighbourhood_map(plane, width, height, &offsets, |values| values.min())
+}
+
+// Helper function taking the maximum under the reflected structuring element at every pixel, ignoring pixels outside of the plane
+fn dilate_plane(plane: &[u8], width: usize, height: usize, element: &StructuringElement) -> Vec<u8> {
+    let offsets = element.offsets().iter().map(|&(dy, dx)| (-dy, -dx)).collect::<Vec<(isize, isize)>>();
+    neighbourhood_map(plane, width, height, &offsets, |values| values.max())
+}
+
+// Helper function reducing the in-bounds values at the given offsets of every pixel
+fn neighbourhood_map<F>(plane: &[u8], width: usize, height: usize, offsets: &[(isize, isize)], reduce: F) -> Vec<u8>
+where F: Fn(&mut dyn Iterator<Item = u8>) -> Option<u8> {
+    let mut new_plane = plane.to_vec();
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut values = offsets.iter()
+                                    .map(|&(dy, dx)| (y + dy, x + dx))
+                                    .filter(|&(ny, nx)| ny >= 0 && nx >= 0 && ny < height as isize && nx < width as isize)
+                                    .map(|(ny, nx)| plane[ny as usize * width + nx as usize]);
+            if let Some(value) = reduce(&mut values) {
+                new_plane[y as usize * width + x as usize] = value;
+            }
+        }
+    }
+    new_plane
+}
+
+// Helper function computing the binary hit-or-miss transform, counting pixels outside of the plane as background
+fn hit_or_miss_plane(plane: &[u8], width: usize, height: usize, hit: &StructuringElement, miss: &StructuringElement) -> Vec<u8> {
+    let (hits, misses) = (hit.offsets(), miss.offsets());
+    let at = |y: isize, x: isize| {
+        if y < 0 || x < 0 || y >= height as isize || x >= width as isize { 0 } else { plane[y as usize * width + x as usize] }
+    };
+
+    let mut new_plane = vec![0; plane.len()];
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let matched = hits.iter().all(|&(dy, dx)| at(y + dy, x + dx) == 1) &&
+                          misses.iter().all(|&(dy, dx)| at(y + dy, x + dx) == 0);
+            new_plane[y as usize * width + x as usize] = matched as u8;
+        }
+    }
+    new_plane
+}
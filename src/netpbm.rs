@@ -0,0 +1,369 @@
+//! Drop-in equivalents of classic Netpbm programs, reading PNM streams from stdin or files and writing them to stdout
+//!
+//! Each program takes its command line arguments (without the program name), a reader standing for stdin and a writer standing for stdout.
+//! Options follow the Netpbm conventions: one or two leading dashes, values given as `-name value` or `-name=value`, and any unique prefix of an option name.
+//! Every program also accepts `-plain` to write the ascii format instead of the binary one.
+//!
+//! The `cli` feature builds them as the `pnmflip`, `pamscale`, `ppmtopgm`, `pgmtopbm`, `pnmcat` and `pnminvert` binaries.
+//!
+//! # Example:
+//! ```
+//! use ductr::netpbm;
+//!
+//! let mut output = Vec::new();
+//! netpbm::pnmflip(&["-leftright".to_string()], &mut &b"P2\n3 1\n255\n1 2 3"[..], &mut output).unwrap();
+//! assert_eq!(output, b"P5\n3 1\n255\n\x03\x02\x01");
+//! ```
+
+use std::{collections::HashMap, fs, io::{self, Read, Write}, process::ExitCode};
+use crate::{AnymapImage, DitherMethod, GreyscaleMethod};
+use crate::compose::concat;
+
+/// Signature shared by every program of the module, see `run`
+pub type Program = fn(&[String], &mut dyn Read, &mut dyn Write) -> Result<(), String>;
+
+/// Runs a program with the process arguments, stdin and stdout, printing errors as `<name>: <message>` on stderr
+///
+/// # Example:
+/// ```no_run
+/// use ductr::netpbm;
+///
+/// fn main() -> std::process::ExitCode {
+///     netpbm::run("pnminvert", netpbm::pnminvert)
+/// }
+/// ```
+pub fn run(name: &str, program: Program) -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let mut stdout = io::stdout().lock();
+    match program(&args, &mut io::stdin().lock(), &mut stdout).and_then(|_| stdout.flush().map_err(|e| format!("Error: could not write stdout: {}", e))) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}: {}", name, e.trim_start_matches("Error: "));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Flips and rotates an image, applying every operation in the given order (`pnmflip`)
+///
+/// # Options
+/// - `-leftright` / `-lr`, `-topbottom` / `-tb`, `-transpose` / `-xy`
+/// - `-rotate90` / `-r90` / `-ccw`, `-rotate180` / `-r180`, `-rotate270` / `-r270` / `-cw`
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let args = ["-cw".to_string(), "-plain".to_string()];
+/// let mut output = Vec::new();
+/// netpbm::pnmflip(&args, &mut &b"P2\n2 1\n255\n1 2"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P2\n1 2\n255\n1\n2");
+/// ```
+pub fn pnmflip(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[], &["leftright", "lr", "topbottom", "tb", "transpose", "xy", "rotate90", "r90", "ccw",
+                                             "rotate180", "r180", "rotate270", "r270", "cw", "plain"])?;
+    let mut image = read_input(options.single_file()?, stdin)?;
+    for flag in &options.flags {
+        match *flag {
+            "leftright" | "lr" => image.flip_horizontal(),
+            "topbottom" | "tb" => image.flip_vertical(),
+            "transpose" | "xy" => image.transpose(),
+            "rotate90" | "r90" | "ccw" => image.rotate_counterclockwise(),
+            "rotate180" | "r180" => image.rotate_180(),
+            "rotate270" | "r270" | "cw" => image.rotate_clockwise(),
+            _ => (),
+        }
+    }
+    write_output(&image, &options, stdout)
+}
+
+/// Resizes an image by a scale factor or to given dimensions (`pamscale`)
+///
+/// # Options
+/// - a scale factor as first argument when no other size is given
+/// - `-xsize` / `-width`, `-ysize` / `-height` in pixels, `-xscale`, `-yscale` as factors, a missing dimension keeps the aspect ratio
+/// - `-reduce N` shrinks both dimensions N times
+/// - `-xyfit W H` / `-xysize W H` and `-xyfill W H` scale as large as fits inside, or as small as covers, the given box
+///
+/// # Notes
+/// - shrinking averages the covered pixels like the default Netpbm pixel mixing, enlarging repeats the nearest pixel
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let args = ["0.5".to_string()];
+/// let mut output = Vec::new();
+/// netpbm::pamscale(&args, &mut &b"P2\n4 2\n255\n0 100 200 250\n0 100 200 250"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P5\n2 1\n255\n\x32\xe1");
+/// ```
+pub fn pamscale(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[("xsize", 1), ("width", 1), ("ysize", 1), ("height", 1), ("xscale", 1), ("yscale", 1),
+                                         ("reduce", 1), ("xyfit", 2), ("xysize", 2), ("xyfill", 2)], &["plain"])?;
+    let sized = !options.values.is_empty();
+    let (factor, files) = match (sized, options.files.split_first()) {
+        (false, Some((factor, files))) => (Some(parse_number::<f64>(factor, "scale factor")?), files),
+        (false, None) => return Err("Error: a scale factor or a size option is required".to_string()),
+        (true, _) => (None, options.files.as_slice()),
+    };
+    if files.len() > 1 { return Err("Error: too many arguments".to_string()) }
+    let image = read_input(files.first().map(String::as_str), stdin)?;
+    let (height, width) = image.dimensions();
+    let (height, width) = (height as f64, width as f64);
+
+    let number = |names: &[&str]| -> Result<Option<f64>, String> {
+        names.iter()
+             .find_map(|name| options.values.get(name))
+             .map(|values| parse_number::<f64>(&values[0], names[0]))
+             .transpose()
+    };
+    let boxed = |names: &[&str]| -> Result<Option<(f64, f64)>, String> {
+        names.iter()
+             .find_map(|name| options.values.get(name))
+             .map(|values| Ok((parse_number::<f64>(&values[0], names[0])?, parse_number::<f64>(&values[1], names[0])?)))
+             .transpose()
+    };
+
+    let (x_scale, y_scale) = if let Some(factor) = factor {
+        (factor, factor)
+    } else if let Some(reduce) = number(&["reduce"])? {
+        (1.0 / reduce, 1.0 / reduce)
+    } else if let Some((w, h)) = boxed(&["xyfit", "xysize"])? {
+        let scale = (w / width).min(h / height);
+        (scale, scale)
+    } else if let Some((w, h)) = boxed(&["xyfill"])? {
+        let scale = (w / width).max(h / height);
+        (scale, scale)
+    } else {
+        let x = number(&["xsize", "width"])?.map(|w| w / width).or(number(&["xscale"])?);
+        let y = number(&["ysize", "height"])?.map(|h| h / height).or(number(&["yscale"])?);
+        match (x, y) {
+            (Some(x), Some(y)) => (x, y),
+            (Some(x), None) => (x, x),
+            (None, Some(y)) => (y, y),
+            (None, None) => return Err("Error: a scale factor or a size option is required".to_string()),
+        }
+    };
+    if !(x_scale > 0.0 && y_scale > 0.0 && x_scale.is_finite() && y_scale.is_finite()) { return Err("Error: scale factors must be positive".to_string()) }
+
+    let (new_height, new_width) = ((height * y_scale + 0.5).floor(), (width * x_scale + 0.5).floor());
+    // usize::MAX as f64 rounds up to 2^64, which no longer fits
+    if new_height >= usize::MAX as f64 || new_width >= usize::MAX as f64 { return Err(format!("Error: scaled dimensions are too large ({}x{})", new_width, new_height)) }
+    let (new_height, new_width) = ((new_height as usize).max(1), (new_width as usize).max(1));
+    write_output(&image.resize(new_height, new_width)?, &options, stdout)
+}
+
+/// Converts an image to PGM using Rec. 601 luminance (`ppmtopgm`)
+///
+/// # Notes
+/// - PBM images become a PGM with a saturation of 1, PGM images pass through unchanged
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let mut output = Vec::new();
+/// netpbm::ppmtopgm(&[], &mut &b"P3\n1 1\n255\n255 0 0"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P5\n1 1\n255\n\x4c");
+/// ```
+pub fn ppmtopgm(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[], &["plain"])?;
+    let image = read_input(options.single_file()?, stdin)?;
+    write_output(&image.to_pgm(GreyscaleMethod::Rec601)?, &options, stdout)
+}
+
+/// Converts an image to PBM, dithering its luminance (`pgmtopbm`)
+///
+/// # Options
+/// - `-floyd` / `-fs` for Floyd-Steinberg error diffusion (default)
+/// - `-dither8` / `-d8` for ordered dithering with an 8x8 Bayer matrix
+/// - `-threshold` for a plain threshold
+/// - `-value` in the range of `0.0..=1.0` moves the threshold of `-floyd` and `-threshold` away from half brightness,
+///   `-dither8` ignores it like Netpbm does
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let args = ["-threshold".to_string(), "-plain".to_string()];
+/// let mut output = Vec::new();
+/// netpbm::pgmtopbm(&args, &mut &b"P2\n3 1\n255\n20 127 200"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P1\n3 1\n1 1 0");
+/// ```
+pub fn pgmtopbm(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[("value", 1)], &["floyd", "fs", "dither8", "d8", "threshold", "plain"])?;
+    let image = read_input(options.single_file()?, stdin)?;
+    if image.magic_number == "pbm" { return write_output(&image, &options, stdout) }
+
+    let method = match options.flags.iter().rev().find(|flag| **flag != "plain") {
+        Some(&"threshold") => None,
+        Some(&"dither8" | &"d8") => Some(DitherMethod::Bayer(8)),
+        _ => Some(DitherMethod::FloydSteinberg),
+    };
+    let value = options.values.get("value").map(|values| parse_number::<f64>(&values[0], "value")).transpose()?.unwrap_or(0.5);
+    if !(0.0..=1.0).contains(&value) { return Err(format!("Error: -value must fall in the range of 0.0..=1.0 ({})", value)) }
+    let pbm = match method {
+        Some(DitherMethod::Bayer(size)) => image.dither_to_pbm(DitherMethod::Bayer(size))?,
+        Some(method) => image.dither_to_pbm_at(method, value)?,
+        None => {
+            // pixels strictly darker than value * saturation become black
+            let level = (value * image.saturation as f64).ceil() as usize;
+            match level {
+                0 => AnymapImage::pbm(vec![0; image.width*image.height], image.height, image.width)?,
+                level => image.threshold(level - 1)?,
+            }
+        },
+    };
+    write_output(&pbm, &options, stdout)
+}
+
+/// Joins images side by side or stacks them (`pnmcat`)
+///
+/// # Options
+/// - `-leftright` / `-lr` or `-topbottom` / `-tb` (one is required)
+/// - `-jtop`, `-jbottom`, `-jleft`, `-jright` to justify smaller images (centered by default)
+/// - `-white` (default) or `-black` for the space around smaller images
+///
+/// # Notes
+/// - `-` reads one of the images from stdin, images sharing a format keep it while mixed formats are promoted to PPM
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let path = std::env::temp_dir().join("ductr_pnmcat_doc.pgm");
+/// std::fs::write(&path, b"P2\n1 1\n255\n9").unwrap();
+///
+/// let args = ["-lr".to_string(), "-jtop".to_string(), "-plain".to_string(), "-".to_string(), path.display().to_string()];
+/// let mut output = Vec::new();
+/// netpbm::pnmcat(&args, &mut &b"P2\n1 2\n255\n1\n2"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P2\n2 2\n255\n1 9\n2 255");
+/// ```
+pub fn pnmcat(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[], &["leftright", "lr", "topbottom", "tb", "jtop", "jbottom", "jleft", "jright", "white", "black", "plain"])?;
+    let has = |names: &[&str]| options.flags.iter().any(|flag| names.contains(flag));
+    let horizontal = match (has(&["leftright", "lr"]), has(&["topbottom", "tb"])) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => return Err("Error: exactly one of -leftright or -topbottom is required".to_string()),
+    };
+    let align = match (horizontal, has(&["jtop", "jleft"]), has(&["jbottom", "jright"])) {
+        (_, true, true) => return Err("Error: conflicting justification options".to_string()),
+        (_, true, false) => 0.0,
+        (_, false, true) => 1.0,
+        _ => 0.5,
+    };
+    let background = if has(&["black"]) { [0; 3] } else { [255; 3] };
+
+    if options.files.iter().filter(|file| *file == "-").count() > 1 { return Err("Error: stdin can only be read once".to_string()) }
+    let images = match options.files.is_empty() {
+        true => vec![read_input(None, stdin)?],
+        false => options.files.iter().map(|file| read_input(Some(file), stdin)).collect::<Result<Vec<AnymapImage>, String>>()?,
+    };
+    write_output(&concat(&images, horizontal, 0, align, background)?, &options, stdout)
+}
+
+/// Inverts every pixel of an image (`pnminvert`)
+///
+/// # Example:
+/// ```
+/// use ductr::netpbm;
+///
+/// let mut output = Vec::new();
+/// netpbm::pnminvert(&["-plain".to_string()], &mut &b"P2\n2 1\n15\n0 5"[..], &mut output).unwrap();
+/// assert_eq!(output, b"P2\n2 1\n15\n15 10");
+/// ```
+pub fn pnminvert(args: &[String], stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<(), String> {
+    let options = Options::parse(args, &[], &["plain"])?;
+    let mut image = read_input(options.single_file()?, stdin)?;
+    image.invert();
+    write_output(&image, &options, stdout)
+}
+
+// Parsed Netpbm style command line: option values, flags in the order they were given, and file arguments
+struct Options {
+    values: HashMap<&'static str, Vec<String>>,
+    flags: Vec<&'static str>,
+    files: Vec<String>,
+}
+
+impl Options {
+    // Helper function parsing arguments given the (name, value count) options and the flags a program accepts
+    fn parse(args: &[String], valued: &[(&'static str, usize)], flags: &[&'static str]) -> Result<Options, String> {
+        let mut options = Options { values: HashMap::new(), flags: Vec::new(), files: Vec::new() };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--").or(arg.strip_prefix('-')).filter(|option| !option.is_empty()) else {
+                options.files.push(arg.clone());
+                continue
+            };
+            // negative numbers are arguments, not options
+            if option.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                options.files.push(arg.clone());
+                continue
+            }
+
+            let (name, inline) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            let names = valued.iter().map(|(name, _)| *name).chain(flags.iter().copied());
+            let matched = match names.clone().find(|known| *known == name) {
+                Some(known) => known,
+                None => {
+                    let candidates = names.filter(|known| known.starts_with(name)).collect::<Vec<&'static str>>();
+                    match candidates.as_slice() {
+                        [known] => *known,
+                        [] => return Err(format!("Error: unknown option -{}", name)),
+                        _ => return Err(format!("Error: ambiguous option -{} ({})", name, candidates.join(", "))),
+                    }
+                },
+            };
+
+            match valued.iter().find(|(known, _)| *known == matched) {
+                Some(&(known, count)) => {
+                    let mut values = inline.into_iter().collect::<Vec<String>>();
+                    while values.len() < count {
+                        values.push(args.next().ok_or(format!("Error: option -{} needs {} value(s)", known, count))?.clone());
+                    }
+                    options.values.insert(known, values);
+                },
+                None => {
+                    if inline.is_some() { return Err(format!("Error: option -{} does not take a value", matched)) }
+                    let flag = flags.iter().copied().find(|flag| *flag == matched).unwrap_or_default();
+                    options.flags.push(flag);
+                },
+            }
+        }
+        Ok(options)
+    }
+
+    // Helper function returning the only file argument, or `None` to read stdin
+    fn single_file(&self) -> Result<Option<&str>, String> {
+        match self.files.as_slice() {
+            [] => Ok(None),
+            [file] => Ok(Some(file)),
+            _ => Err("Error: too many arguments".to_string()),
+        }
+    }
+}
+
+// Helper function reading an image from a file, or from stdin for `None` and `-`
+fn read_input(path: Option<&str>, mut stdin: &mut dyn Read) -> Result<AnymapImage, String> {
+    match path {
+        None | Some("-") => AnymapImage::read_from(&mut stdin),
+        Some(path) => AnymapImage::read_from_bytes(&fs::read(path).map_err(|e| format!("Error: could not read {}: {}", path, e))?),
+    }
+}
+
+// Helper function writing an image in the binary format, or in the ascii one when `-plain` was given
+fn write_output(image: &AnymapImage, options: &Options, mut stdout: &mut dyn Write) -> Result<(), String> {
+    match options.flags.contains(&"plain") {
+        true => image.write_ascii_to(&mut stdout),
+        false => image.write_binary_to(&mut stdout),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Error: invalid number for {}: {:?}", name, value))
+}
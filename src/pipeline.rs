@@ -0,0 +1,397 @@
+//! Chains of operations described as data, checked against the input format before any pixel is touched
+//!
+//! A `Pipeline` is built step by step, or deserialized from a spec with the `serde` feature (`json` and `toml` add `Pipeline::from_json` and `Pipeline::from_toml`).
+//! The same pipeline can then be applied to a single image or run over many files.
+//!
+//! ```toml
+//! [[steps]]
+//! op = "greyscale"
+//! method = "rec709"
+//!
+//! [[steps]]
+//! op = "resize"
+//! height = 240
+//! width = 320
+//!
+//! [[steps]]
+//! op = "dither"
+//! method = { bayer = 8 }
+//! ```
+
+use std::{fs, path::Path};
+use crate::{AnymapImage, DitherMethod, GreyscaleMethod};
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+/// A single step of a `Pipeline`
+///
+/// With the `serde` feature, steps are tagged by their snake case name in an `op` field (ie. `{ "op": "gaussian_blur", "sigma": 1.5 }`).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(tag = "op", rename_all = "snake_case", deny_unknown_fields))]
+pub enum Operation {
+    /// `AnymapImage::invert`
+    Invert,
+    /// `AnymapImage::greyscale_with`, keeping the format (the method defaults to `Average` in specs)
+    Greyscale {
+        #[cfg_attr(feature = "serde", serde(default = "default_greyscale"))]
+        method: GreyscaleMethod,
+    },
+    /// `AnymapImage::to_pgm` (the method defaults to `Average` in specs)
+    ToPgm {
+        #[cfg_attr(feature = "serde", serde(default = "default_greyscale"))]
+        method: GreyscaleMethod,
+    },
+    /// `AnymapImage::to_format` with "pbm", "pgm" or "ppm"
+    Convert { format: String },
+    /// `AnymapImage::resize`
+    Resize { height: usize, width: usize },
+    /// `AnymapImage::crop`
+    Crop { top: usize, left: usize, height: usize, width: usize },
+    /// `AnymapImage::flip_horizontal`
+    FlipHorizontal,
+    /// `AnymapImage::flip_vertical`
+    FlipVertical,
+    /// `AnymapImage::transpose`
+    Transpose,
+    /// `AnymapImage::rotate_clockwise`
+    RotateClockwise,
+    /// `AnymapImage::rotate_counterclockwise`
+    RotateCounterclockwise,
+    /// `AnymapImage::rotate_180`
+    Rotate180,
+    /// `AnymapImage::box_blur`, PBM formats are not accepted
+    BoxBlur { radius: usize },
+    /// `AnymapImage::gaussian_blur`, PBM formats are not accepted
+    GaussianBlur { sigma: f64 },
+    /// `AnymapImage::median_filter`, PBM formats are not accepted
+    MedianFilter { radius: usize },
+    /// `AnymapImage::adjust_gamma`, PBM formats are not accepted
+    AdjustGamma { gamma: f64 },
+    /// `AnymapImage::brightness_contrast`, PBM formats are not accepted
+    BrightnessContrast { brightness: f64, contrast: f64 },
+    /// `AnymapImage::equalize_histogram`, PBM formats are not accepted
+    EqualizeHistogram,
+    /// `AnymapImage::threshold`, turning the image into a PBM, PBM formats are not accepted
+    Threshold { value: usize },
+    /// `AnymapImage::threshold_otsu`, turning the image into a PBM, PBM formats are not accepted
+    ThresholdOtsu,
+    /// `AnymapImage::dither_to_pbm`, turning the image into a PBM, PBM formats are not accepted
+    Dither { method: DitherMethod },
+}
+
+#[cfg(feature = "serde")]
+fn default_greyscale() -> GreyscaleMethod {
+    GreyscaleMethod::Average
+}
+
+impl Operation {
+
+    // Helper function returning the snake case name of the operation, as used in specs and errors
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Invert => "invert",
+            Operation::Greyscale { .. } => "greyscale",
+            Operation::ToPgm { .. } => "to_pgm",
+            Operation::Convert { .. } => "convert",
+            Operation::Resize { .. } => "resize",
+            Operation::Crop { .. } => "crop",
+            Operation::FlipHorizontal => "flip_horizontal",
+            Operation::FlipVertical => "flip_vertical",
+            Operation::Transpose => "transpose",
+            Operation::RotateClockwise => "rotate_clockwise",
+            Operation::RotateCounterclockwise => "rotate_counterclockwise",
+            Operation::Rotate180 => "rotate180",
+            Operation::BoxBlur { .. } => "box_blur",
+            Operation::GaussianBlur { .. } => "gaussian_blur",
+            Operation::MedianFilter { .. } => "median_filter",
+            Operation::AdjustGamma { .. } => "adjust_gamma",
+            Operation::BrightnessContrast { .. } => "brightness_contrast",
+            Operation::EqualizeHistogram => "equalize_histogram",
+            Operation::Threshold { .. } => "threshold",
+            Operation::ThresholdOtsu => "threshold_otsu",
+            Operation::Dither { .. } => "dither",
+        }
+    }
+
+    // Helper function checking the operation against the format it receives, returning the format it produces
+    // Arguments that do not depend on the pixels are checked here too, so that a bad step fails before any work is done
+    fn output_format(&self, format: &'static str) -> Result<&'static str, String> {
+        let greyscale_channel = |method: &GreyscaleMethod| match method {
+            GreyscaleMethod::Channel(channel) if *channel > 2 => Err(format!("Error: channel index must fall in the range of 0..3 ({})", channel)),
+            _ => Ok(()),
+        };
+        let not_pbm = |action: &str| match format {
+            "pbm" => Err(format!("Error: pbm images cannot {}", action)),
+            _ => Ok(()),
+        };
+
+        match self {
+            Operation::Greyscale { method } => greyscale_channel(method)?,
+            Operation::ToPgm { method } => { greyscale_channel(method)?; return Ok("pgm") },
+            Operation::Convert { format } => return match format.as_str() {
+                "pbm" => Ok("pbm"),
+                "pgm" => Ok("pgm"),
+                "ppm" => Ok("ppm"),
+                _ => Err(format!("Error: unknown format {:?}, expected pbm, pgm or ppm", format)),
+            },
+            Operation::Resize { height, width } | Operation::Crop { height, width, .. } if *height == 0 || *width == 0 => {
+                return Err(format!("Error: dimensions must be larger than 0 ({}x{})", width, height))
+            },
+            Operation::BoxBlur { .. } => not_pbm("be blurred")?,
+            Operation::GaussianBlur { sigma } => {
+                not_pbm("be blurred")?;
+                if sigma.is_nan() || *sigma <= 0.0 { return Err(format!("Error: gaussian sigma must be greater than 0 ({})", sigma)) }
+            },
+            Operation::MedianFilter { .. } => not_pbm("be median filtered")?,
+            Operation::AdjustGamma { gamma } => {
+                not_pbm("have tones adjusted")?;
+                if gamma.is_nan() || *gamma <= 0.0 { return Err(format!("Error: gamma must be greater than 0 ({})", gamma)) }
+            },
+            Operation::BrightnessContrast { brightness, contrast } => {
+                not_pbm("have tones adjusted")?;
+                if contrast.is_nan() || *contrast < 0.0 { return Err(format!("Error: contrast must not be negative ({})", contrast)) }
+                if brightness.is_nan() { return Err("Error: brightness must be a number".to_string()) }
+            },
+            Operation::EqualizeHistogram => not_pbm("be equalized")?,
+            Operation::Threshold { .. } | Operation::ThresholdOtsu => { not_pbm("be thresholded")?; return Ok("pbm") },
+            Operation::Dither { method } => { not_pbm("be dithered")?; method.validate()?; return Ok("pbm") },
+            _ => (),
+        }
+        Ok(format)
+    }
+
+    // Helper function applying the operation to an image
+    fn apply(&self, image: &mut AnymapImage) -> Result<(), String> {
+        match self {
+            Operation::Invert => image.invert(),
+            Operation::Greyscale { method } => image.greyscale_with(*method)?,
+            Operation::ToPgm { method } => *image = image.to_pgm(*method)?,
+            Operation::Convert { format } => *image = image.to_format(format)?,
+            Operation::Resize { height, width } => *image = image.resize(*height, *width)?,
+            Operation::Crop { top, left, height, width } => *image = image.crop(*top, *left, *height, *width)?,
+            Operation::FlipHorizontal => image.flip_horizontal(),
+            Operation::FlipVertical => image.flip_vertical(),
+            Operation::Transpose => image.transpose(),
+            Operation::RotateClockwise => image.rotate_clockwise(),
+            Operation::RotateCounterclockwise => image.rotate_counterclockwise(),
+            Operation::Rotate180 => image.rotate_180(),
+            Operation::BoxBlur { radius } => image.box_blur(*radius)?,
+            Operation::GaussianBlur { sigma } => image.gaussian_blur(*sigma)?,
+            Operation::MedianFilter { radius } => image.median_filter(*radius)?,
+            Operation::AdjustGamma { gamma } => image.adjust_gamma(*gamma)?,
+            Operation::BrightnessContrast { brightness, contrast } => image.brightness_contrast(*brightness, *contrast)?,
+            Operation::EqualizeHistogram => image.equalize_histogram()?,
+            Operation::Threshold { value } => *image = image.threshold(*value)?,
+            Operation::ThresholdOtsu => *image = image.threshold_otsu()?,
+            Operation::Dither { method } => *image = image.dither_to_pbm(*method)?,
+        }
+        Ok(())
+    }
+}
+
+/// An ordered list of operations applied one after the other
+///
+/// With the `serde` feature, a pipeline deserializes from a map holding its operations in a `steps` list.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(deny_unknown_fields))]
+pub struct Pipeline {
+    steps: Vec<Operation>,
+}
+
+impl Pipeline {
+
+    /// Creates an empty pipeline, which returns images unchanged
+    pub fn new() -> Self {
+        Pipeline { steps: Vec::new() }
+    }
+
+    /// Appends any operation to the pipeline
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::DitherMethod;
+    /// use ductr::pipeline::{Operation, Pipeline};
+    ///
+    /// let pipeline = Pipeline::new().then(Operation::Rotate180)
+    ///                               .then(Operation::Dither { method: DitherMethod::Bayer(4) });
+    /// assert_eq!(pipeline.steps().len(), 2);
+    /// ```
+    pub fn then(mut self, operation: Operation) -> Self {
+        self.steps.push(operation);
+        self
+    }
+
+    /// Appends `Operation::Invert`
+    pub fn invert(self) -> Self {
+        self.then(Operation::Invert)
+    }
+
+    /// Appends `Operation::Greyscale`
+    pub fn greyscale(self, method: GreyscaleMethod) -> Self {
+        self.then(Operation::Greyscale { method })
+    }
+
+    /// Appends `Operation::Convert`
+    pub fn convert(self, format: &str) -> Self {
+        self.then(Operation::Convert { format: format.to_string() })
+    }
+
+    /// Appends `Operation::Resize`
+    pub fn resize(self, height: usize, width: usize) -> Self {
+        self.then(Operation::Resize { height, width })
+    }
+
+    /// Appends `Operation::Crop`
+    pub fn crop(self, top: usize, left: usize, height: usize, width: usize) -> Self {
+        self.then(Operation::Crop { top, left, height, width })
+    }
+
+    /// Appends `Operation::BoxBlur`
+    pub fn box_blur(self, radius: usize) -> Self {
+        self.then(Operation::BoxBlur { radius })
+    }
+
+    /// Appends `Operation::GaussianBlur`
+    pub fn gaussian_blur(self, sigma: f64) -> Self {
+        self.then(Operation::GaussianBlur { sigma })
+    }
+
+    /// Returns the operations of the pipeline, in the order they are applied
+    pub fn steps(&self) -> &[Operation] {
+        &self.steps
+    }
+
+    /// Checks every step against the format it will receive, without touching any image
+    ///
+    /// # Rules for arguments:
+    /// - `magic_number` is the format of the input, one of "pbm", "pgm" or "ppm"
+    ///
+    /// # Notes
+    /// - returns the format of the images the pipeline produces
+    /// - errors name the failing step, counted from 1
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::pipeline::Pipeline;
+    ///
+    /// let pipeline = Pipeline::new().convert("pbm").gaussian_blur(1.0);
+    /// assert_eq!(pipeline.validate("ppm").unwrap_err(), "Error: step 2 (gaussian_blur): pbm images cannot be blurred");
+    /// assert_eq!(Pipeline::new().convert("pgm").validate("ppm").unwrap(), "pgm");
+    /// ```
+    pub fn validate(&self, magic_number: &str) -> Result<String, String> {
+        let mut format = match magic_number {
+            "pbm" => "pbm",
+            "pgm" => "pgm",
+            "ppm" => "ppm",
+            _ => return Err(format!("Error: unknown format {:?}, expected pbm, pgm or ppm", magic_number)),
+        };
+        for (idx, step) in self.steps.iter().enumerate() {
+            format = step.output_format(format)
+                         .map_err(|e| format!("Error: step {} ({}): {}", idx + 1, step.name(), e.trim_start_matches("Error: ")))?;
+        }
+        Ok(format.to_string())
+    }
+
+    /// Validates the pipeline against the format of the image, then applies every step to it
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, GreyscaleMethod};
+    /// use ductr::pipeline::Pipeline;
+    ///
+    /// let ppm = AnymapImage::ppm(vec![255, 0, 0, 0, 0, 255], 255, 1, 2).unwrap();
+    /// let pipeline = Pipeline::new().greyscale(GreyscaleMethod::Rec601)
+    ///                               .convert("pgm")
+    ///                               .invert()
+    ///                               .resize(2, 2);
+    ///
+    /// let pgm = pipeline.apply(ppm).unwrap();
+    /// assert_eq!(pgm.get_buffer(), vec![179, 226, 179, 226]);
+    /// ```
+    pub fn apply(&self, mut image: AnymapImage) -> Result<AnymapImage, String> {
+        self.validate(&image.magic_number)?;
+        for (idx, step) in self.steps.iter().enumerate() {
+            step.apply(&mut image)
+                .map_err(|e| format!("Error: step {} ({}): {}", idx + 1, step.name(), e.trim_start_matches("Error: ")))?;
+        }
+        Ok(image)
+    }
+
+    /// Runs the pipeline over many files, writing each result in binary format into `output_dir`
+    ///
+    /// # Rules for arguments:
+    /// - every input must be a PNM file in ascii or binary format
+    /// - `output_dir` is created when missing
+    ///
+    /// # Notes
+    /// - results keep the name of their input with the extension of the format they end up in (ie. `cat.ppm` becomes `cat.pbm` after a threshold)
+    /// - a failing file does not stop the others, each input gets either the path written or the error
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use ductr::pipeline::Pipeline;
+    ///
+    /// let pipeline = Pipeline::new().invert().gaussian_blur(2.0);
+    /// for result in pipeline.run_files(&["tests/images/cat_binary.ppm", "tests/images/red.ppm"], "out") {
+    ///     match result {
+    ///         Ok(path) => println!("wrote {}", path),
+    ///         Err(e) => eprintln!("{}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn run_files<P: AsRef<Path>>(&self, inputs: &[P], output_dir: &str) -> Vec<Result<String, String>> {
+        if let Err(e) = fs::create_dir_all(output_dir) {
+            let error = format!("Error: could not create {}: {}", output_dir, e);
+            return inputs.iter().map(|_| Err(error.clone())).collect()
+        }
+
+        inputs.iter()
+              .map(|input| {
+                  let input = input.as_ref();
+                  self.run_file(input, Path::new(output_dir))
+                      .map_err(|e| format!("Error: {}: {}", input.display(), e.trim_start_matches("Error: ")))
+              })
+              .collect()
+    }
+
+    // Helper function running the pipeline over a single file, returning the path written
+    fn run_file(&self, input: &Path, output_dir: &Path) -> Result<String, String> {
+        let bytes = fs::read(input).map_err(|e| format!("Error: could not read file: {}", e))?;
+        let image = self.apply(AnymapImage::read_from_bytes(&bytes)?)?;
+
+        let name = input.file_stem().ok_or("Error: input is not a file".to_string())?;
+        let output = output_dir.join(name).with_extension(&image.magic_number);
+        let mut file = fs::File::create(&output).map_err(|e| format!("Error: could not create file: {}", e))?;
+        image.write_binary_to(&mut file)?;
+        Ok(output.display().to_string())
+    }
+
+    /// Reads a pipeline from a JSON spec
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::pipeline::Pipeline;
+    ///
+    /// let spec = r#"{ "steps": [{ "op": "invert" }, { "op": "box_blur", "radius": 2 }] }"#;
+    /// assert_eq!(Pipeline::from_json(spec).unwrap(), Pipeline::new().invert().box_blur(2));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(spec: &str) -> Result<Self, String> {
+        serde_json::from_str(spec).map_err(|e| format!("Error: invalid pipeline spec: {}", e))
+    }
+
+    /// Reads a pipeline from a TOML spec
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::pipeline::Pipeline;
+    ///
+    /// let spec = "[[steps]]\nop = \"resize\"\nheight = 10\nwidth = 20\n";
+    /// assert_eq!(Pipeline::from_toml(spec).unwrap(), Pipeline::new().resize(10, 20));
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(spec: &str) -> Result<Self, String> {
+        toml::from_str(spec).map_err(|e| format!("Error: invalid pipeline spec: {}", e))
+    }
+}
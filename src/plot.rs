@@ -0,0 +1,411 @@
+//! Simple charts rendered into 8 bit PPM images, for looking at metrics without a GUI
+//!
+//! Every chart is drawn on a white background with axes, grid lines, tick labels, an optional title and axis
+//! labels, and a legend naming every labelled series. Series without a color take the next color of a
+//! 10 color palette.
+//!
+//! ```no_run
+//! use ductr::plot::{self, PlotOptions, Series};
+//!
+//! let loss = (0..50).map(|step| (step as f64, 1.0 / (step as f64 + 1.0))).collect::<Vec<(f64, f64)>>();
+//! let options = PlotOptions { title: "training".to_string(), x_label: "step".to_string(), y_label: "loss".to_string(), ..PlotOptions::default() };
+//!
+//! let chart = plot::line_chart(&[Series::new("loss", loss)], &options).unwrap();
+//! chart.write_as_binary("loss.ppm").unwrap();
+//! ```
+
+use crate::AnymapImage;
+use crate::draw::{self, Color, TextStyle};
+
+// colors given to series that do not set their own
+const PALETTE: [[u8; 3]; 10] = [[31, 119, 180], [255, 127, 14], [44, 160, 44], [214, 39, 40], [148, 103, 189],
+                                [140, 86, 75], [227, 119, 194], [127, 127, 127], [188, 189, 34], [23, 190, 207]];
+const BLACK: Color = Color::Rgb([0, 0, 0]);
+const GRID: Color = Color::Rgb([225, 225, 225]);
+
+/// Layout and labels shared by every chart
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlotOptions {
+    /// width of the image in pixels, at least 100
+    pub width: usize,
+    /// height of the image in pixels, at least 80
+    pub height: usize,
+    /// text drawn centered above the chart, left out when empty
+    pub title: String,
+    /// text drawn below the horizontal axis, left out when empty
+    pub x_label: String,
+    /// text drawn above the vertical axis, left out when empty
+    pub y_label: String,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions { width: 480, height: 320, title: String::new(), x_label: String::new(), y_label: String::new() }
+    }
+}
+
+/// A named set of (x, y) points drawn as one line, set of markers or set of bars
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    /// name shown in the legend, series with an empty label are left out of it
+    pub label: String,
+    /// (x, y) values, non finite values are skipped
+    pub points: Vec<(f64, f64)>,
+    /// color of the series, `None` picks the next palette color
+    pub color: Option<[u8; 3]>,
+}
+
+impl Series {
+
+    /// Creates a series of (x, y) points using the next palette color
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::plot::Series;
+    ///
+    /// let series = Series::new("squares", vec![(1.0, 1.0), (2.0, 4.0), (3.0, 9.0)]);
+    /// assert_eq!(series.color, None);
+    /// ```
+    pub fn new(label: &str, points: Vec<(f64, f64)>) -> Self {
+        Series { label: label.to_string(), points, color: None }
+    }
+
+    /// Creates a series from y values, using their index as x value
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::plot::Series;
+    ///
+    /// let series = Series::from_values("accuracy", &[0.5, 0.75, 0.875]);
+    /// assert_eq!(series.points, vec![(0.0, 0.5), (1.0, 0.75), (2.0, 0.875)]);
+    /// ```
+    pub fn from_values(label: &str, values: &[f64]) -> Self {
+        Series::new(label, values.iter().enumerate().map(|(x, &y)| (x as f64, y)).collect())
+    }
+
+    /// Returns the series with the given color instead of a palette color
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::plot::Series;
+    ///
+    /// let series = Series::from_values("error", &[3.0, 1.0]).with_color([200, 0, 0]);
+    /// assert_eq!(series.color, Some([200, 0, 0]));
+    /// ```
+    pub fn with_color(mut self, color: [u8; 3]) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// Draws every series as a line connecting its points in order
+///
+/// # Rules for arguments:
+/// - at least one series must hold a finite point
+/// - `options` must be at least 100x80 pixels
+///
+/// # Example:
+/// ```
+/// use ductr::plot::{self, PlotOptions, Series};
+///
+/// let chart = plot::line_chart(&[Series::from_values("loss", &[4.0, 2.0, 1.0, 0.5])], &PlotOptions::default()).unwrap();
+/// assert_eq!(chart.dimensions(), (320, 480));
+/// ```
+pub fn line_chart(series: &[Series], options: &PlotOptions) -> Result<AnymapImage, String> {
+    let (x_range, y_range) = data_ranges(series)?;
+    let (mut image, frame) = Frame::draw(options, Axis::Numeric(x_range), y_range)?;
+    for (idx, line) in series.iter().enumerate() {
+        let color = Color::Rgb(series_color(line, idx));
+        let points = finite_points(line).map(|point| frame.to_pixel(point)).collect::<Vec<(isize, isize)>>();
+        for segment in points.windows(2) {
+            image.draw_line_aa(segment[0], segment[1], color)?;
+        }
+        if let [point] = points[..] {
+            image.fill_circle(point, 1, color)?;
+        }
+    }
+    draw_legend(&mut image, &frame, series)?;
+    Ok(image)
+}
+
+/// Draws every series as a set of round markers
+///
+/// # Rules for arguments:
+/// - at least one series must hold a finite point
+/// - `options` must be at least 100x80 pixels
+///
+/// # Example:
+/// ```
+/// use ductr::plot::{self, PlotOptions, Series};
+///
+/// let points = vec![(0.2, 1.5), (0.4, 1.1), (0.9, 2.8)];
+/// let chart = plot::scatter_plot(&[Series::new("samples", points)], &PlotOptions::default()).unwrap();
+/// assert_eq!(chart.dimensions(), (320, 480));
+/// ```
+pub fn scatter_plot(series: &[Series], options: &PlotOptions) -> Result<AnymapImage, String> {
+    let (x_range, y_range) = data_ranges(series)?;
+    let (mut image, frame) = Frame::draw(options, Axis::Numeric(x_range), y_range)?;
+    for (idx, markers) in series.iter().enumerate() {
+        let color = Color::Rgb(series_color(markers, idx));
+        for point in finite_points(markers) {
+            image.fill_circle(frame.to_pixel(point), 2, color)?;
+        }
+    }
+    draw_legend(&mut image, &frame, series)?;
+    Ok(image)
+}
+
+/// Draws a grouped bar chart, with one bar per series for every category
+///
+/// # Rules for arguments:
+/// - `categories` must not be empty, point `n` of every series (its y value) is the bar of category `n`
+/// - `options` must be at least 100x80 pixels
+///
+/// # Notes
+/// - bars grow from 0, so negative values point downwards
+///
+/// # Example:
+/// ```
+/// use ductr::plot::{self, PlotOptions, Series};
+///
+/// let cpu = Series::from_values("cpu", &[12.0, 30.0, 18.0]);
+/// let gpu = Series::from_values("gpu", &[3.0, 4.5, 2.0]);
+/// let chart = plot::bar_chart(&["load", "render", "save"], &[cpu, gpu], &PlotOptions::default()).unwrap();
+/// assert_eq!(chart.dimensions(), (320, 480));
+/// ```
+pub fn bar_chart(categories: &[&str], series: &[Series], options: &PlotOptions) -> Result<AnymapImage, String> {
+    if categories.is_empty() { return Err("Error: bar chart needs at least one category".to_string()) }
+    let values = series.iter()
+                       .flat_map(|bars| bars.points.iter().take(categories.len()).map(|point| point.1))
+                       .filter(|value| value.is_finite())
+                       .collect::<Vec<f64>>();
+    if values.is_empty() { return Err("Error: nothing to plot, every series is empty or non finite".to_string()) }
+    let y_range = (values.iter().fold(0.0f64, |low, &v| low.min(v)), values.iter().fold(0.0f64, |high, &v| high.max(v)));
+
+    let labels = categories.iter().map(|category| category.to_string()).collect();
+    let (mut image, frame) = Frame::draw(options, Axis::Categories(labels), y_range)?;
+    let group = (frame.right - frame.left) as f64 / categories.len() as f64;
+    let bar = group * 0.8 / series.len().max(1) as f64;
+    for (idx, bars) in series.iter().enumerate() {
+        let color = Color::Rgb(series_color(bars, idx));
+        for (category, point) in bars.points.iter().take(categories.len()).enumerate() {
+            if !point.1.is_finite() { continue; }
+            let left = frame.left as f64 + group * (category as f64 + 0.1) + bar * idx as f64;
+            let (_, top) = frame.to_pixel((0.0, point.1));
+            let (_, base) = frame.to_pixel((0.0, 0.0));
+            image.fill_rectangle((left.round() as isize, top), ((left + bar).round() as isize - 1, base), color)?;
+        }
+    }
+    draw_legend(&mut image, &frame, series)?;
+    Ok(image)
+}
+
+/// Counts values into equally wide bins over their range and draws the counts as bars
+///
+/// # Rules for arguments:
+/// - `values` must hold at least one finite value, non finite values are skipped
+/// - `bins` must be greater than 0
+/// - `options` must be at least 100x80 pixels
+///
+/// # Example:
+/// ```
+/// use ductr::plot::{self, PlotOptions};
+///
+/// let samples = (0..1000).map(|i| ((i * 7919) % 1000) as f64 / 100.0).collect::<Vec<f64>>();
+/// let chart = plot::histogram(&samples, 20, &PlotOptions::default()).unwrap();
+/// assert_eq!(chart.dimensions(), (320, 480));
+/// ```
+pub fn histogram(values: &[f64], bins: usize, options: &PlotOptions) -> Result<AnymapImage, String> {
+    if bins == 0 { return Err("Error: histogram needs at least one bin".to_string()) }
+    let finite = values.iter().copied().filter(|value| value.is_finite()).collect::<Vec<f64>>();
+    if finite.is_empty() { return Err("Error: nothing to plot, every value is non finite".to_string()) }
+
+    let (low, high) = widen(min_max(finite.iter().copied()));
+    let width = (high - low) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for value in finite {
+        counts[(((value - low) / width) as usize).min(bins - 1)] += 1;
+    }
+
+    let tallest = *counts.iter().max().unwrap_or(&0) as f64;
+    let (mut image, frame) = Frame::draw(options, Axis::Numeric((low, high)), (0.0, tallest))?;
+    let color = Color::Rgb(PALETTE[0]);
+    for (bin, &count) in counts.iter().enumerate() {
+        if count == 0 { continue; }
+        let (left, top) = frame.to_pixel((low + width * bin as f64, count as f64));
+        let (right, base) = frame.to_pixel((low + width * (bin + 1) as f64, 0.0));
+        image.fill_rectangle((left, top), ((right - 1).max(left), base), color)?;
+    }
+    Ok(image)
+}
+
+// horizontal axis of a chart, numeric with ticks or one slot per category
+enum Axis {
+    Numeric((f64, f64)),
+    Categories(Vec<String>),
+}
+
+// pixel bounds of the plotting area (inclusive) and the data ranges mapped onto them
+struct Frame {
+    left: isize,
+    top: isize,
+    right: isize,
+    bottom: isize,
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Frame {
+
+    // Helper function drawing the background, labels, grid and axes of a chart, returning the image and its plotting area
+    fn draw(options: &PlotOptions, axis: Axis, y_range: (f64, f64)) -> Result<(AnymapImage, Frame), String> {
+        if options.width < 100 || options.height < 80 { return Err(format!("Error: charts must be at least 100x80 pixels ({}x{})", options.width, options.height)) }
+
+        let style = TextStyle::default();
+        let (y_low, y_high, y_step) = nice_ticks(widen(y_range));
+        let y_ticks = tick_values(y_low, y_high, y_step);
+        let y_labels = y_ticks.iter().map(|&tick| format_tick(tick, y_step)).collect::<Vec<String>>();
+        let label_width = y_labels.iter().map(|label| draw::measure_text(label, &style).0).max().unwrap_or(0);
+
+        let line = 12;
+        let top = 10 + if options.title.is_empty() { 0 } else { line } + if options.y_label.is_empty() { 0 } else { line };
+        let bottom = options.height as isize - 1 - (20 + if options.x_label.is_empty() { 0 } else { line });
+        let left = label_width as isize + 12;
+        let right = options.width as isize - 12;
+
+        let (x_low, x_high, x_step) = match &axis {
+            Axis::Numeric(range) => nice_ticks(widen(*range)),
+            Axis::Categories(labels) => (0.0, labels.len() as f64, 1.0),
+        };
+        let frame = Frame { left, top, right, bottom, x: (x_low, x_high), y: (y_low, y_high) };
+        let mut image = AnymapImage::ppm(vec![255; options.width * options.height * 3], 255, options.height, options.width)?;
+
+        // horizontal grid lines and vertical tick labels
+        for (tick, label) in y_ticks.iter().zip(&y_labels) {
+            let (_, y) = frame.to_pixel((x_low, *tick));
+            image.draw_line((left, y), (right, y), GRID, 1)?;
+            image.draw_line((left - 3, y), (left, y), BLACK, 1)?;
+            let (width, height) = draw::measure_text(label, &style);
+            image.draw_text(left - 5 - width as isize, y - height as isize / 2, label, BLACK, 1)?;
+        }
+
+        // vertical grid lines and horizontal tick labels
+        let x_ticks = match axis {
+            Axis::Numeric(_) => tick_values(x_low, x_high, x_step).into_iter()
+                                                               .map(|tick| (tick, format_tick(tick, x_step), true))
+                                                               .collect::<Vec<(f64, String, bool)>>(),
+            Axis::Categories(labels) => labels.into_iter()
+                                              .enumerate()
+                                              .map(|(idx, label)| (idx as f64 + 0.5, label, false))
+                                              .collect(),
+        };
+        for (tick, label, grid) in x_ticks {
+            let (x, _) = frame.to_pixel((tick, y_low));
+            if grid { image.draw_line((x, top), (x, bottom), GRID, 1)?; }
+            image.draw_line((x, bottom), (x, bottom + 3), BLACK, 1)?;
+            let (width, _) = draw::measure_text(&label, &style);
+            image.draw_text(x - width as isize / 2, bottom + 6, &label, BLACK, 1)?;
+        }
+
+        image.draw_polyline(&[(left, top), (left, bottom), (right, bottom)], BLACK, 1)?;
+
+        if !options.title.is_empty() {
+            let (width, _) = draw::measure_text(&options.title, &style);
+            image.draw_text((options.width as isize - width as isize) / 2, 6, &options.title, BLACK, 1)?;
+        }
+        if !options.y_label.is_empty() {
+            image.draw_text(4, top - line - 2, &options.y_label, BLACK, 1)?;
+        }
+        if !options.x_label.is_empty() {
+            let (width, _) = draw::measure_text(&options.x_label, &style);
+            image.draw_text((left + right - width as isize) / 2, bottom + 6 + line, &options.x_label, BLACK, 1)?;
+        }
+        Ok((image, frame))
+    }
+
+    // Helper function mapping a data point onto a pixel of the plotting area
+    fn to_pixel(&self, (x, y): (f64, f64)) -> (isize, isize) {
+        let px = self.left as f64 + (x - self.x.0) / (self.x.1 - self.x.0) * (self.right - self.left) as f64;
+        let py = self.bottom as f64 - (y - self.y.0) / (self.y.1 - self.y.0) * (self.bottom - self.top) as f64;
+        (px.round() as isize, py.round() as isize)
+    }
+}
+
+// Helper function drawing a boxed legend in the top right corner of the plotting area for every labelled series
+fn draw_legend(image: &mut AnymapImage, frame: &Frame, series: &[Series]) -> Result<(), String> {
+    let style = TextStyle::default();
+    let entries = series.iter()
+                        .enumerate()
+                        .filter(|(_, entry)| !entry.label.is_empty())
+                        .map(|(idx, entry)| (entry.label.as_str(), series_color(entry, idx)))
+                        .collect::<Vec<(&str, [u8; 3])>>();
+    if entries.is_empty() { return Ok(()) }
+
+    let text_width = entries.iter().map(|(label, _)| draw::measure_text(label, &style).0).max().unwrap_or(0) as isize;
+    let (width, height) = (text_width + 18, entries.len() as isize * 11 + 5);
+    let (left, top) = (frame.right - width - 4, frame.top + 4);
+    image.fill_rectangle((left, top), (left + width, top + height), Color::Rgb([255, 255, 255]))?;
+    image.draw_rectangle((left, top), (left + width, top + height), Color::Rgb([160, 160, 160]), 1)?;
+    for (idx, (label, color)) in entries.iter().enumerate() {
+        let y = top + 4 + idx as isize * 11;
+        image.fill_rectangle((left + 4, y + 1), (left + 10, y + 6), Color::Rgb(*color))?;
+        image.draw_text(left + 14, y, label, BLACK, 1)?;
+    }
+    Ok(())
+}
+
+// Helper function returning the color of a series, falling back on the palette color of its position
+fn series_color(series: &Series, idx: usize) -> [u8; 3] {
+    series.color.unwrap_or(PALETTE[idx % PALETTE.len()])
+}
+
+// Helper function iterating over the finite points of a series
+fn finite_points(series: &Series) -> impl Iterator<Item = (f64, f64)> + '_ {
+    series.points.iter().copied().filter(|point| point.0.is_finite() && point.1.is_finite())
+}
+
+// (low, high) bounds of the values along one axis
+type Range = (f64, f64);
+
+// Helper function returning the (x, y) ranges covered by the finite points of every series
+fn data_ranges(series: &[Series]) -> Result<(Range, Range), String> {
+    let points = series.iter().flat_map(finite_points).collect::<Vec<(f64, f64)>>();
+    if points.is_empty() { return Err("Error: nothing to plot, every series is empty or non finite".to_string()) }
+    Ok((min_max(points.iter().map(|point| point.0)), min_max(points.iter().map(|point| point.1))))
+}
+
+// Helper function returning the smallest and largest value of an iterator
+fn min_max(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)))
+}
+
+// Helper function giving an empty range some width, so single values still land inside the chart
+fn widen((low, high): (f64, f64)) -> (f64, f64) {
+    if high > low { return (low, high) }
+    let margin = if low == 0.0 { 1.0 } else { low.abs() * 0.1 };
+    (low - margin, high + margin)
+}
+
+// Helper function rounding a range outwards onto "nice" ticks (1, 2 or 5 times a power of ten), about 5 of them
+fn nice_ticks((low, high): (f64, f64)) -> (f64, f64, f64) {
+    let rough = (high - low) / 5.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].iter()
+                                    .map(|factor| factor * magnitude)
+                                    .find(|&step| step >= rough)
+                                    .unwrap_or(10.0 * magnitude);
+    ((low / step).floor() * step, (high / step).ceil() * step, step)
+}
+
+// Helper function listing the ticks from `low` to `high`
+fn tick_values(low: f64, high: f64, step: f64) -> Vec<f64> {
+    let count = ((high - low) / step).round() as usize;
+    (0..=count).map(|idx| low + step * idx as f64).collect()
+}
+
+// Helper function formatting a tick with just enough decimals to tell neighbouring ticks apart
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    let text = format!("{:.*}", decimals, value);
+    if text.starts_with('-') && text[1..].chars().all(|c| c == '0' || c == '.') { text[1..].to_string() } else { text }
+}
@@ -0,0 +1,406 @@
+use std::{collections::HashMap, io::Write};
+use crate::{AnymapImage, DitherMethod};
+use crate::geometry::area_resize;
+
+/// Color support assumed when rendering an image as terminal text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiMode {
+    /// 24 bit color escape codes with upper half blocks, two pixels per character
+    TrueColor,
+    /// 256 color palette escape codes with upper half blocks, two pixels per character
+    Color256,
+    /// no escape codes, characters from a luminance ramp (assumes light text on a dark background)
+    Ascii,
+}
+
+// characters from darkest to brightest used by the ascii mode
+const RAMP: &[u8] = b" .:-=+*#%@";
+
+// largest payload of a single kitty graphics escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+impl AnymapImage {
+
+    /// Renders the AnymapImage struct as 24 bit color terminal text, see `to_ansi_with` for other modes
+    ///
+    /// # Rules for arguments:
+    /// - `max_cols` must be greater than 0, wider images are shrunk to fit (keeping their aspect ratio)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// // red pixel above a blue pixel
+    /// let ppm = AnymapImage::ppm(vec![255, 0, 0, 0, 0, 255], 255, 2, 1).unwrap();
+    /// assert_eq!(ppm.to_ansi(80).unwrap(), "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}\x1b[0m\n");
+    /// ```
+    pub fn to_ansi(&self, max_cols: usize) -> Result<String, String> {
+        self.to_ansi_with(max_cols, AnsiMode::TrueColor)
+    }
+
+    /// Renders the AnymapImage struct as terminal text, one line per text row ending in a newline
+    ///
+    /// # Rules for arguments:
+    /// - `max_cols` must be greater than 0, wider images are shrunk to fit (keeping their aspect ratio)
+    ///
+    /// # Notes
+    /// - block modes draw two pixels per character, the top one as foreground and the bottom one as background,
+    ///   and reset the colors at the end of every line
+    /// - the ascii mode averages two rows per character, since characters are about twice as tall as they are wide
+    /// - images are never enlarged
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, AnsiMode};
+    ///
+    /// let gradient = AnymapImage::pgm((0..8).map(|i| i * 36).collect(), 255, 2, 4).unwrap();
+    /// assert_eq!(gradient.to_ansi_with(4, AnsiMode::Ascii).unwrap(), "-=+*\n");
+    /// assert_eq!(gradient.to_ansi_with(2, AnsiMode::Ascii).unwrap(), "-*\n");
+    /// ```
+    pub fn to_ansi_with(&self, max_cols: usize, mode: AnsiMode) -> Result<String, String> {
+        if max_cols == 0 { return Err("Error: max_cols must be greater than 0".to_string()) }
+        let mut text = String::new();
+        if self.width == 0 || self.height == 0 { return Ok(text) }
+
+        let width = self.width.min(max_cols);
+        let height = ((self.height as f64 * width as f64 / self.width as f64).round() as usize).max(1);
+        let pixels = area_resize(&self.rgb_base(), 3, self.width, self.height, width, height)?;
+
+        if mode == AnsiMode::Ascii {
+            let rows = height.div_ceil(2);
+            let shrunk = area_resize(&pixels, 3, width, height, width, rows)?;
+            for row in shrunk.chunks(width * 3) {
+                for pixel in row.chunks(3) {
+                    let luminance = 0.299 * pixel[0] as f64 + 0.587 * pixel[1] as f64 + 0.114 * pixel[2] as f64;
+                    text.push(RAMP[(luminance / 255.0 * (RAMP.len() - 1) as f64).round() as usize] as char);
+                }
+                text.push('\n');
+            }
+            return Ok(text)
+        }
+
+        let code = |pixel: &[u8], layer: u8| match mode {
+            AnsiMode::TrueColor => format!("\x1b[{};2;{};{};{}m", layer, pixel[0], pixel[1], pixel[2]),
+            _ => format!("\x1b[{};5;{}m", layer, palette_index(pixel)),
+        };
+        for y in (0..height).step_by(2) {
+            let (mut foreground, mut background) = (String::new(), String::new());
+            for x in 0..width {
+                let top = &pixels[(y*width + x)*3..(y*width + x)*3 + 3];
+                let next = code(top, 38);
+                if next != foreground { text.push_str(&next); foreground = next; }
+
+                let next = match y + 1 < height {
+                    true => code(&pixels[((y + 1)*width + x)*3..((y + 1)*width + x)*3 + 3], 48),
+                    false => "\x1b[49m".to_string(),
+                };
+                if next != background { text.push_str(&next); background = next; }
+                text.push('\u{2580}');
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        Ok(text)
+    }
+
+    /// Encodes the AnymapImage struct as a Sixel escape sequence (xterm, WezTerm, foot, mlterm...)
+    ///
+    /// # Rules for arguments:
+    /// - `colors` is the size of the palette the image is quantized to, in the range of `2..=256`
+    /// - `dither` is used when mapping pixels onto the palette (see `DitherMethod`)
+    /// - the image must not be empty
+    ///
+    /// # Notes
+    /// - the palette is chosen with median cut, images holding no more colors than `colors` keep their exact colors
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, DitherMethod};
+    ///
+    /// let pbm = AnymapImage::pbm(vec![1; 6], 6, 1).unwrap();
+    /// let sixel = pbm.to_sixel(16, DitherMethod::Nearest).unwrap();
+    /// assert_eq!(sixel, "\x1bPq\"1;1;1;6#0;2;0;0;0#0~\x1b\\");
+    /// ```
+    pub fn to_sixel(&self, colors: usize, dither: DitherMethod) -> Result<String, String> {
+        let mut encoded = Vec::new();
+        self.write_sixel(&mut encoded, colors, dither)?;
+        String::from_utf8(encoded).map_err(|e| format!("Error: sixel output is not valid utf-8: {:?}", e))
+    }
+
+    /// Writes the AnymapImage struct as a Sixel escape sequence to any writer, see `to_sixel`
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use ductr::{AnymapImage, DitherMethod};
+    ///
+    /// let ppm = AnymapImage::read_from_binary("tests/images/cat_binary.ppm").unwrap();
+    /// ppm.write_sixel(&mut std::io::stdout(), 256, DitherMethod::FloydSteinberg).unwrap();
+    /// ```
+    pub fn write_sixel<W: Write>(&self, writer: &mut W, colors: usize, dither: DitherMethod) -> Result<(), String> {
+        if !(2..=256).contains(&colors) { return Err(format!("Error: sixel palettes must hold 2..=256 colors ({})", colors)) }
+        if self.width == 0 || self.height == 0 { return Err("Error: empty images cannot be displayed".to_string()) }
+
+        let pixels = self.rgb_base();
+        let palette = median_cut(&pixels, colors);
+        let mut quantized = AnymapImage::ppm(pixels, 255, self.height, self.width)?;
+        quantized.dither_to_palette(&palette, dither)?;
+        let lookup = palette.iter().enumerate().map(|(idx, &color)| (color, idx)).collect::<HashMap<[u8; 3], usize>>();
+        let indices = quantized.buffer.chunks(3).map(|p| lookup[&[p[0], p[1], p[2]]]).collect::<Vec<usize>>();
+
+        let mut sixel = format!("\x1bPq\"1;1;{};{}", self.width, self.height);
+        for (idx, color) in palette.iter().enumerate() {
+            let percent = color.map(|c| (c as usize * 100 + 127) / 255);
+            sixel.push_str(&format!("#{};2;{};{};{}", idx, percent[0], percent[1], percent[2]));
+        }
+
+        for band in (0..self.height).step_by(6) {
+            let rows = (self.height - band).min(6);
+            let mut used = (0..rows).flat_map(|row| indices[(band + row)*self.width..(band + row + 1)*self.width].iter().copied())
+                                    .collect::<Vec<usize>>();
+            used.sort_unstable();
+            used.dedup();
+            for (position, &color) in used.iter().enumerate() {
+                if position > 0 { sixel.push('$'); }
+                sixel.push_str(&format!("#{}", color));
+                let columns = (0..self.width).map(|x| {
+                                                 let bits = (0..rows).filter(|&row| indices[(band + row)*self.width + x] == color)
+                                                                     .fold(0u8, |bits, row| bits | 1 << row);
+                                                 (63 + bits) as char
+                                             })
+                                             .collect::<Vec<char>>();
+                push_runs(&mut sixel, &columns);
+            }
+            sixel.push('-');
+        }
+        sixel.pop();
+        sixel.push_str("\x1b\\");
+        writer.write_all(sixel.as_bytes()).map_err(|e| format!("Error: could not write sixel output: {:?}", e))
+    }
+
+    /// Encodes the AnymapImage struct as a kitty graphics protocol escape sequence (kitty, WezTerm, Konsole...)
+    ///
+    /// # Rules for arguments:
+    /// - the image must not be empty
+    ///
+    /// # Notes
+    /// - pixels are sent as raw 8 bit RGB, split over several escape sequences of at most 4096 base64 characters
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let ppm = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+    /// assert_eq!(ppm.to_kitty().unwrap(), "\x1b_Ga=T,f=24,s=1,v=1,m=0;/wAA\x1b\\");
+    /// ```
+    pub fn to_kitty(&self) -> Result<String, String> {
+        let mut encoded = Vec::new();
+        self.write_kitty(&mut encoded)?;
+        String::from_utf8(encoded).map_err(|e| format!("Error: kitty output is not valid utf-8: {:?}", e))
+    }
+
+    /// Writes the AnymapImage struct as a kitty graphics protocol escape sequence to any writer, see `to_kitty`
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use ductr::AnymapImage;
+    ///
+    /// let ppm = AnymapImage::read_from_binary("tests/images/cat_binary.ppm").unwrap();
+    /// ppm.write_kitty(&mut std::io::stdout()).unwrap();
+    /// ```
+    pub fn write_kitty<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 { return Err("Error: empty images cannot be displayed".to_string()) }
+
+        let payload = base64(&self.rgb_base());
+        let chunks = payload.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<&[u8]>>();
+        let mut kitty = String::new();
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let more = (idx + 1 < chunks.len()) as u8;
+            let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+            match idx {
+                0 => kitty.push_str(&format!("\x1b_Ga=T,f=24,s={},v={},m={};{}\x1b\\", self.width, self.height, more, chunk)),
+                _ => kitty.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk)),
+            }
+        }
+        writer.write_all(kitty.as_bytes()).map_err(|e| format!("Error: could not write kitty output: {:?}", e))
+    }
+
+    /// Encodes the AnymapImage struct as an iTerm2 inline image escape sequence (iTerm2, WezTerm, mintty...)
+    ///
+    /// # Rules for arguments:
+    /// - the image must not be empty
+    ///
+    /// # Notes
+    /// - the image is sent as an uncompressed PNG file, displayed at its size in pixels
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![0, 255], 255, 1, 2).unwrap();
+    /// let iterm = pgm.to_iterm().unwrap();
+    /// assert!(iterm.starts_with("\x1b]1337;File=inline=1;size=75;width=2px;height=1px;preserveAspectRatio=1:iVBORw0KGgo"));
+    /// assert!(iterm.ends_with('\x07'));
+    /// ```
+    pub fn to_iterm(&self) -> Result<String, String> {
+        let mut encoded = Vec::new();
+        self.write_iterm(&mut encoded)?;
+        String::from_utf8(encoded).map_err(|e| format!("Error: iterm output is not valid utf-8: {:?}", e))
+    }
+
+    /// Writes the AnymapImage struct as an iTerm2 inline image escape sequence to any writer, see `to_iterm`
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use ductr::AnymapImage;
+    ///
+    /// let ppm = AnymapImage::read_from_binary("tests/images/cat_binary.ppm").unwrap();
+    /// ppm.write_iterm(&mut std::io::stdout()).unwrap();
+    /// ```
+    pub fn write_iterm<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 { return Err("Error: empty images cannot be displayed".to_string()) }
+
+        let png = png(&self.rgb_base(), self.width, self.height);
+        let iterm = format!("\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=1:{}\x07",
+                            png.len(), self.width, self.height, base64(&png));
+        writer.write_all(iterm.as_bytes()).map_err(|e| format!("Error: could not write iterm output: {:?}", e))
+    }
+}
+
+// Helper function picking a palette of at most `colors` colors with median cut
+// Boxes of colors are split at the median of their widest channel until there are enough of them
+fn median_cut(pixels: &[u8], colors: usize) -> Vec<[u8; 3]> {
+    let mut counts = HashMap::new();
+    for pixel in pixels.chunks(3) {
+        *counts.entry([pixel[0], pixel[1], pixel[2]]).or_insert(0usize) += 1;
+    }
+    let mut unique = counts.into_iter().collect::<Vec<([u8; 3], usize)>>();
+    unique.sort_unstable();
+    if unique.len() <= colors { return unique.into_iter().map(|(color, _)| color).collect() }
+
+    let widest = |bucket: &[([u8; 3], usize)]| (0..3).map(|c| {
+                                                          let low = bucket.iter().map(|(color, _)| color[c]).min().unwrap_or(0);
+                                                          let high = bucket.iter().map(|(color, _)| color[c]).max().unwrap_or(0);
+                                                          (high - low, c)
+                                                      })
+                                                      .max()
+                                                      .unwrap_or((0, 0));
+    let mut buckets = vec![unique];
+    while buckets.len() < colors {
+        let Some((idx, channel)) = buckets.iter()
+                                          .enumerate()
+                                          .filter(|(_, bucket)| bucket.len() > 1)
+                                          .map(|(idx, bucket)| (widest(bucket), bucket.iter().map(|(_, count)| count).sum::<usize>(), idx))
+                                          .max()
+                                          .map(|((_, channel), _, idx)| (idx, channel)) else { break };
+        let mut bucket = buckets.swap_remove(idx);
+        bucket.sort_unstable_by_key(|(color, _)| color[channel]);
+        let half = bucket.iter().map(|(_, count)| count).sum::<usize>() / 2;
+        let mut seen = 0;
+        let split = bucket.iter()
+                          .position(|(_, count)| { seen += count; seen >= half })
+                          .unwrap_or(0)
+                          .clamp(0, bucket.len() - 2) + 1;
+        let upper = bucket.split_off(split);
+        buckets.push(bucket);
+        buckets.push(upper);
+    }
+
+    buckets.iter()
+           .map(|bucket| {
+               let total = bucket.iter().map(|(_, count)| count).sum::<usize>();
+               let mut sums = [0usize; 3];
+               for (color, count) in bucket {
+                   for c in 0..3 { sums[c] += color[c] as usize * count; }
+               }
+               sums.map(|sum| ((sum + total / 2) / total) as u8)
+           })
+           .collect()
+}
+
+// Helper function appending sixel characters, repeating runs of 4 or more as `!<count><character>`
+fn push_runs(sixel: &mut String, columns: &[char]) {
+    let mut idx = 0;
+    while idx < columns.len() {
+        let run = columns[idx..].iter().take_while(|&&c| c == columns[idx]).count();
+        if run >= 4 {
+            sixel.push_str(&format!("!{}{}", run, columns[idx]));
+        } else {
+            (0..run).for_each(|_| sixel.push(columns[idx]));
+        }
+        idx += run;
+    }
+}
+
+// Helper function encoding bytes as standard padded base64
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let value = (group[0] as u32) << 16 | (*group.get(1).unwrap_or(&0) as u32) << 8 | *group.get(2).unwrap_or(&0) as u32;
+        for position in 0..4 {
+            if position <= group.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6*position) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Helper function encoding 8 bit RGB pixels as a PNG file, using uncompressed deflate blocks
+fn png(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((width*3 + 1) * height);
+    for row in pixels.chunks(width * 3) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(0xFFFF).collect::<Vec<&[u8]>>();
+    for (idx, block) in blocks.iter().enumerate() {
+        zlib.push((idx + 1 == blocks.len()) as u8);
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), &byte| { let a = (a + byte as u32) % 65521; (a, (b + a) % 65521) });
+    zlib.extend_from_slice(&(b << 16 | a).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut file = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+    for (kind, data) in [(b"IHDR", header), (b"IDAT", zlib), (b"IEND", Vec::new())] {
+        file.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = file.len();
+        file.extend_from_slice(kind);
+        file.extend_from_slice(&data);
+        let crc = crc32(&file[start..]);
+        file.extend_from_slice(&crc.to_be_bytes());
+    }
+    file
+}
+
+// Helper function computing the CRC-32 checksum used by PNG chunks
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 == 1 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 })
+    })
+}
+
+// Helper function returning the closest color of the xterm 256 color palette (6x6x6 cube or grey ramp)
+fn palette_index(pixel: &[u8]) -> u8 {
+    let level = |value: u8| if value < 48 { 0 } else if value < 115 { 1 } else { (value as usize - 35) / 40 };
+    let cube_value = |level: usize| if level == 0 { 0 } else { 55 + 40 * level as i32 };
+    let (r, g, b) = (level(pixel[0]), level(pixel[1]), level(pixel[2]));
+    let cube = [cube_value(r), cube_value(g), cube_value(b)];
+
+    let average = pixel.iter().map(|&v| v as usize).sum::<usize>() / 3;
+    let grey_level = if average > 238 { 23 } else { average.saturating_sub(3) / 10 };
+    let grey = 8 + 10 * grey_level as i32;
+
+    let distance = |color: [i32; 3]| color.iter().zip(pixel).map(|(&a, &b)| (a - b as i32).pow(2)).sum::<i32>();
+    if distance([grey; 3]) < distance(cube) { 232 + grey_level as u8 } else { (16 + 36*r + 6*g + b) as u8 }
+}
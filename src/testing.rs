@@ -0,0 +1,176 @@
+//! Golden image helpers for tests
+//!
+//! `assert_image_eq!` compares two images directly, while `assert_image_matches_snapshot!` compares an image
+//! with a snapshot stored under `tests/snapshots` of the calling crate.
+//!
+//! When a snapshot is missing or does not match, the image is written next to it as `<name>.new.<ext>` along
+//! with a `<name>.diff.ppm` heatmap. Running the tests with the `DUCTR_BLESS` environment variable set to `1`
+//! overwrites the snapshots with the current images instead of failing. `DUCTR_SNAPSHOT_DIR` moves the
+//! snapshots of `assert_image_matches_snapshot!` to another directory, while `check_snapshot` always uses the
+//! directory it is given.
+
+use std::{env, fs, path::{Path, PathBuf}};
+use crate::AnymapImage;
+use crate::compare::{self, DiffOptions, DiffStyle};
+
+/// Environment variable that, when set to `1`, rewrites snapshots instead of comparing against them
+pub const BLESS_VAR: &str = "DUCTR_BLESS";
+
+/// Environment variable replacing the default snapshot directory of `assert_image_matches_snapshot!`
+pub const SNAPSHOT_DIR_VAR: &str = "DUCTR_SNAPSHOT_DIR";
+
+/// Asserts that two images share the same format and dimensions, and that no channel differs by more than a tolerance
+///
+/// # Example:
+/// ```
+/// use ductr::{AnymapImage, assert_image_eq};
+///
+/// let a = AnymapImage::pgm(vec![10, 20], 255, 1, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![11, 20], 255, 1, 2).unwrap();
+///
+/// assert_image_eq!(a, a);
+/// assert_image_eq!(a, b, 1);
+/// ```
+#[macro_export]
+macro_rules! assert_image_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_image_eq!($actual, $expected, 0)
+    };
+    ($actual:expr, $expected:expr, $tolerance:expr $(,)?) => {
+        if let Err(e) = $crate::testing::check_image_eq(&$actual, &$expected, $tolerance) {
+            panic!("{}", e);
+        }
+    };
+}
+
+/// Asserts that an image matches the snapshot `name` stored under `tests/snapshots` of the calling crate
+///
+/// # Notes
+/// - the snapshots are read from `DUCTR_SNAPSHOT_DIR` instead when it is set
+/// - see the `testing` module for how snapshots are written and blessed
+///
+/// # Example:
+/// ```no_run
+/// use ductr::{AnymapImage, assert_image_matches_snapshot};
+///
+/// let red = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+///
+/// assert_image_matches_snapshot!(red, "red_pixel");
+/// assert_image_matches_snapshot!(red, "red_pixel", 2);
+/// ```
+#[macro_export]
+macro_rules! assert_image_matches_snapshot {
+    ($image:expr, $name:expr $(,)?) => {
+        $crate::assert_image_matches_snapshot!($image, $name, 0)
+    };
+    ($image:expr, $name:expr, $tolerance:expr $(,)?) => {
+        if let Err(e) = $crate::testing::check_snapshot(&$image, &$crate::testing::snapshot_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots")), $name, $tolerance) {
+            panic!("{}", e);
+        }
+    };
+}
+
+/// Compares two images, returning a description of the mismatch as an error
+///
+/// # Rules for arguments:
+/// - `tolerance` is the largest per channel difference that still counts as equal
+///
+/// # Example:
+/// ```
+/// use ductr::AnymapImage;
+/// use ductr::testing::check_image_eq;
+///
+/// let a = AnymapImage::pgm(vec![10, 20], 255, 1, 2).unwrap();
+/// let b = AnymapImage::pgm(vec![10, 90], 255, 1, 2).unwrap();
+/// assert!(check_image_eq(&a, &b, 5).is_err());
+/// ```
+pub fn check_image_eq(actual: &AnymapImage, expected: &AnymapImage, tolerance: usize) -> Result<(), String> {
+    let diff = compare::diff_image(actual, expected, &DiffOptions { style: DiffStyle::Heatmap, tolerance })
+                       .map_err(|e| format!("images are not comparable: {}", e))?;
+    match diff.bounding_box {
+        None => Ok(()),
+        Some((top_left, bottom_right)) => Err(format!("images differ: {} pixels exceed a tolerance of {} between (row, column) {:?} and {:?}",
+                                                      diff.differing_pixels, tolerance, top_left, bottom_right)),
+    }
+}
+
+/// Compares an image with the snapshot `name` stored in `directory`, returning a description of the mismatch as an error
+///
+/// # Rules for arguments:
+/// - `directory` is always used as given, `DUCTR_SNAPSHOT_DIR` is only read by `snapshot_dir`
+/// - `name` must be a valid file name, without extension (the extension follows the image format)
+/// - `tolerance` is the largest per channel difference that still counts as equal
+///
+/// # Notes
+/// - on failure the image is written as `<name>.new.<ext>`, and a `<name>.diff.ppm` heatmap is written when both are comparable
+/// - with `DUCTR_BLESS=1` the snapshot is overwritten with the image and the check passes
+///
+/// # Example:
+/// ```no_run
+/// use ductr::AnymapImage;
+/// use ductr::testing::check_snapshot;
+///
+/// let red = AnymapImage::ppm(vec![255, 0, 0], 255, 1, 1).unwrap();
+/// check_snapshot(&red, "tests/snapshots", "red_pixel", 0).unwrap();
+/// ```
+pub fn check_snapshot(image: &AnymapImage, directory: &str, name: &str, tolerance: usize) -> Result<(), String> {
+    let directory = PathBuf::from(directory);
+    let extension = image.magic_number.as_str();
+    let snapshot = directory.join(format!("{}.{}", name, extension));
+    let new = directory.join(format!("{}.new.{}", name, extension));
+    let diff = directory.join(format!("{}.diff.ppm", name));
+
+    fs::create_dir_all(&directory).map_err(|e| format!("Error: could not create snapshot directory {}: {}", directory.display(), e))?;
+
+    if env::var(BLESS_VAR).map(|v| v == "1").unwrap_or(false) {
+        for leftover in [&new, &diff] {
+            let _ = fs::remove_file(leftover);
+        }
+        return image.write_as_binary(path_str(&snapshot)?);
+    }
+
+    if !snapshot.exists() {
+        image.write_as_binary(path_str(&new)?)?;
+        return Err(format!("snapshot {} does not exist, wrote the current image to {} (run with {}=1 to accept it)",
+                           snapshot.display(), new.display(), BLESS_VAR));
+    }
+
+    let expected = AnymapImage::read_from_binary(path_str(&snapshot)?)?;
+    match check_image_eq(image, &expected, tolerance) {
+        Ok(()) => {
+            for leftover in [&new, &diff] {
+                let _ = fs::remove_file(leftover);
+            }
+            Ok(())
+        },
+        Err(e) => {
+            image.write_as_binary(path_str(&new)?)?;
+            if let Ok(difference) = compare::diff_image(image, &expected, &DiffOptions { style: DiffStyle::Heatmap, tolerance }) {
+                difference.image.write_as_binary(path_str(&diff)?)?;
+            }
+            Err(format!("snapshot {} does not match: {}, wrote the current image to {} (run with {}=1 to accept it)",
+                        snapshot.display(), e, new.display(), BLESS_VAR))
+        }
+    }
+}
+
+/// Returns the snapshot directory set through `DUCTR_SNAPSHOT_DIR`, or `default` when the variable is not set
+///
+/// # Example:
+/// ```
+/// use ductr::testing::snapshot_dir;
+///
+/// std::env::remove_var("DUCTR_SNAPSHOT_DIR");
+/// assert_eq!(snapshot_dir("tests/snapshots"), "tests/snapshots");
+///
+/// std::env::set_var("DUCTR_SNAPSHOT_DIR", "/tmp/snapshots");
+/// assert_eq!(snapshot_dir("tests/snapshots"), "/tmp/snapshots");
+/// ```
+pub fn snapshot_dir(default: &str) -> String {
+    env::var(SNAPSHOT_DIR_VAR).unwrap_or_else(|_| default.to_string())
+}
+
+// Helper function converting a path into the string form the io functions expect
+fn path_str(path: &Path) -> Result<&str, String> {
+    path.to_str().ok_or_else(|| format!("Error: snapshot path is not valid unicode ({})", path.display()))
+}
@@ -0,0 +1,213 @@
+use crate::AnymapImage;
+use crate::filter::{box_blur_plane, convolve_line, map_lines};
+
+/// Ways of computing the local threshold used by `AnymapImage::threshold_adaptive`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveMethod {
+    /// plain mean of the block around each pixel
+    Mean,
+    /// gaussian weighted mean of the block around each pixel
+    Gaussian,
+}
+
+impl AnymapImage {
+
+    /// Converts the AnymapImage struct into a PBM mask using a fixed threshold
+    ///
+    /// # Rules for arguments:
+    /// - PPM images are reduced to their luminance before thresholding
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - pixels at or below `value` become black (1), brighter pixels become white (0)
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![0, 100, 101, 255], 255, 2, 2).unwrap();
+    /// let pbm = pgm.threshold(100).unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 0, 0]);
+    /// ```
+    pub fn threshold(&self, value: usize) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be thresholded".to_string()) }
+
+        let mask = self.luminance_levels()
+                       .iter()
+                       .map(|&level| (level <= value) as u8)
+                       .collect();
+        AnymapImage::pbm(mask, self.height, self.width)
+    }
+
+    /// Converts the AnymapImage struct into a PBM mask using the threshold found by Otsu's method
+    ///
+    /// # Rules for arguments:
+    /// - PPM images are reduced to their luminance before thresholding
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - picks the threshold that maximizes the variance between the dark and bright classes of the histogram
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let pgm = AnymapImage::pgm(vec![10, 20, 200, 210], 255, 2, 2).unwrap();
+    /// let pbm = pgm.threshold_otsu().unwrap();
+    /// assert_eq!(pbm.get_buffer(), vec![1, 1, 0, 0]);
+    /// ```
+    pub fn threshold_otsu(&self) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be thresholded".to_string()) }
+        self.threshold(otsu_level(&self.luminance_histogram()))
+    }
+
+    /// Converts the AnymapImage struct into a PBM mask using the threshold found by the triangle method
+    ///
+    /// # Rules for arguments:
+    /// - PPM images are reduced to their luminance before thresholding
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - draws a line from the histogram peak to the far end of its longest tail and picks the level furthest from that line
+    /// - best suited to images with one dominant peak, such as a bright background with a few dark features
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::AnymapImage;
+    ///
+    /// let mut buffer = vec![230; 100];
+    /// buffer[..10].copy_from_slice(&[5, 20, 40, 60, 80, 100, 120, 140, 160, 180]);
+    /// let pgm = AnymapImage::pgm(buffer, 255, 10, 10).unwrap();
+    ///
+    /// let pbm = pgm.threshold_triangle().unwrap();
+    /// assert_eq!(pbm.get_buffer()[0], 1);
+    /// assert_eq!(pbm.get_buffer()[99], 0);
+    /// ```
+    pub fn threshold_triangle(&self) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be thresholded".to_string()) }
+        self.threshold(triangle_level(&self.luminance_histogram()))
+    }
+
+    /// Converts the AnymapImage struct into a PBM mask by comparing each pixel with the mean of its surrounding block
+    ///
+    /// # Rules for arguments:
+    /// - `block_size` must be an odd number greater than 1
+    /// - PPM images are reduced to their luminance before thresholding
+    /// - PBM formats are not accepted
+    ///
+    /// # Notes
+    /// - pixels at or below their local mean minus `offset` become black (1), all others become white (0)
+    /// - the gaussian method uses a sigma of `0.3 * ((block_size - 1) / 2 - 1) + 0.8`
+    ///
+    /// # Example:
+    /// ```
+    /// use ductr::{AnymapImage, AdaptiveMethod};
+    ///
+    /// // dark text pixel on an unevenly lit background
+    /// let buffer = vec![100, 110, 120, 130, 140,
+    ///                   100, 110,  40, 130, 140,
+    ///                   100, 110, 120, 130, 140];
+    /// let pgm = AnymapImage::pgm(buffer, 255, 3, 5).unwrap();
+    ///
+    /// let pbm = pgm.threshold_adaptive(AdaptiveMethod::Mean, 3, 10.0).unwrap();
+    /// assert_eq!(pbm.get_buffer().iter().filter(|&&b| b == 1).count(), 1);
+    /// assert_eq!(pbm.get_buffer()[7], 1);
+    /// ```
+    pub fn threshold_adaptive(&self, method: AdaptiveMethod, block_size: usize, offset: f64) -> Result<AnymapImage, String> {
+        if self.magic_number == "pbm" { return Err("Error: pbm images cannot be thresholded".to_string()) }
+        if block_size < 3 || block_size.is_multiple_of(2) { return Err(format!("Error: block size must be an odd number greater than 1 ({})", block_size)) }
+
+        let plane = self.luminance_plane();
+        let radius = block_size / 2;
+        let local = match method {
+            AdaptiveMethod::Mean => box_blur_plane(&plane, self.width, self.height, radius),
+            AdaptiveMethod::Gaussian => {
+                let sigma = 0.3 * ((block_size as f64 - 1.0) * 0.5 - 1.0) + 0.8;
+                let kernel = (0..block_size).map(|idx| {
+                                                let x = idx as f64 - radius as f64;
+                                                (-(x * x) / (2.0 * sigma * sigma)).exp()
+                                            })
+                                            .collect::<Vec<f64>>();
+                let total: f64 = kernel.iter().sum();
+                let kernel = kernel.iter().map(|weight| weight / total).collect::<Vec<f64>>();
+                let horizontal = map_lines(&plane, self.width, self.height, true, |line| convolve_line(line, &kernel));
+                map_lines(&horizontal, self.width, self.height, false, |line| convolve_line(line, &kernel))
+            }
+        };
+
+        let mask = plane.iter()
+                        .zip(local.iter())
+                        .map(|(value, mean)| (*value <= mean - offset) as u8)
+                        .collect();
+        AnymapImage::pbm(mask, self.height, self.width)
+    }
+
+    // Helper function returning the luminance of every pixel rounded to a whole level
+    fn luminance_levels(&self) -> Vec<usize> {
+        self.luminance_plane().iter().map(|value| value.round() as usize).collect()
+    }
+
+    // Helper function counting the luminance levels of the image into `saturation + 1` bins
+    fn luminance_histogram(&self) -> Vec<u64> {
+        let mut histogram = vec![0; self.saturation + 1];
+        for level in self.luminance_levels() {
+            histogram[level.min(self.saturation)] += 1;
+        }
+        histogram
+    }
+}
+
+// Helper function finding the level maximizing the between-class variance of a histogram
+fn otsu_level(histogram: &[u64]) -> usize {
+    let total: u64 = histogram.iter().sum();
+    let total_sum: f64 = histogram.iter().enumerate().map(|(level, &count)| level as f64 * count as f64).sum();
+
+    let (mut dark_count, mut dark_sum) = (0u64, 0.0);
+    let (mut best_level, mut best_variance) = (0, -1.0);
+    for (level, &count) in histogram.iter().enumerate() {
+        dark_count += count;
+        dark_sum += level as f64 * count as f64;
+        let bright_count = total - dark_count;
+        if dark_count == 0 { continue; }
+        if bright_count == 0 { break; }
+
+        let dark_mean = dark_sum / dark_count as f64;
+        let bright_mean = (total_sum - dark_sum) / bright_count as f64;
+        let variance = dark_count as f64 * bright_count as f64 * (dark_mean - bright_mean).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_level = level;
+        }
+    }
+    best_level
+}
+
+// Helper function finding the threshold level with the triangle method (Zack et al., 1977)
+fn triangle_level(histogram: &[u64]) -> usize {
+    let first = match histogram.iter().position(|&count| count > 0) {
+        Some(first) => first,
+        None => return 0,
+    };
+    let last = histogram.iter().rposition(|&count| count > 0).unwrap_or(first);
+    let peak = (first..=last).max_by_key(|&level| (histogram[level], std::cmp::Reverse(level))).unwrap_or(first);
+
+    // run the line towards the end of the longest tail
+    let tail_is_dark = peak - first > last - peak;
+    let end = if tail_is_dark { first } else { last };
+    if end == peak { return peak }
+
+    let (x1, y1) = (peak as f64, histogram[peak] as f64);
+    let (x2, y2) = (end as f64, histogram[end] as f64);
+    let (levels, mut best_level, mut best_distance) = (peak.min(end)..=peak.max(end), end, -1.0);
+    for level in levels {
+        // distance from the histogram to the peak-tail line (up to a constant factor)
+        let distance = ((y2 - y1) * level as f64 - (x2 - x1) * histogram[level] as f64 + x2 * y1 - y2 * x1).abs();
+        if distance > best_distance {
+            best_distance = distance;
+            best_level = level;
+        }
+    }
+
+    // keep the chosen level on the tail side of the mask
+    if tail_is_dark { best_level } else { best_level.saturating_sub(1) }
+}
@@ -3,6 +3,10 @@
 mod tests {
 
     use ductr::*;
+    use ductr::color::{ColorSpace, YCbCrStandard};
+    use ductr::compare::{DiffOptions, DiffStyle};
+    use ductr::draw::{Color, Font, TextStyle};
+    use ductr::pipeline::{Operation, Pipeline};
 
     #[test]
     fn make_pbm_binary() {
@@ -33,6 +37,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn make_ppm_binary() {
         let mut pixel_map = vec![0; 100*3*100];
         for x in 0..pixel_map.len() {
@@ -45,6 +50,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn make_ppm_ascii() {
         let mut pixel_map = vec![0; 100*3*100];
         for x in 0..pixel_map.len() {
@@ -97,4 +103,884 @@ mod tests {
         x.write_as_ascii("tests/images/read_file_ascii.pnm").unwrap();
     }
 
-}
+    #[test]
+    fn blur_keeps_flat_image() {
+        let mut x = AnymapImage::ppm(vec![90; 12*3*8], 255, 8, 12).unwrap();
+        x.box_blur(2).unwrap();
+        x.gaussian_blur(0.8).unwrap();
+        x.gaussian_blur(6.0).unwrap();
+        assert_eq!(vec![90; 12*3*8], x.get_buffer());
+
+        let mut pbm = AnymapImage::pbm(vec![1; 4*4], 4, 4).unwrap();
+        assert!(pbm.box_blur(1).is_err());
+        assert!(pbm.gaussian_blur(1.0).is_err());
+    }
+
+    #[test]
+    fn median_filter_matches_sorting() {
+        let (height, width, radius) = (9, 11, 2);
+        let pixel_map = (0..height*width).map(|i| ((i * 37 + 11) % 251) as u8).collect::<Vec<u8>>();
+        let mut x = AnymapImage::pgm(pixel_map.clone(), 255, height, width).unwrap();
+        x.median_filter(radius).unwrap();
+
+        let at = |y: isize, x: isize| pixel_map[y.clamp(0, height as isize - 1) as usize * width + x.clamp(0, width as isize - 1) as usize];
+        for y in 0..height as isize {
+            for x_idx in 0..width as isize {
+                let mut window = Vec::new();
+                for dy in -(radius as isize)..=radius as isize {
+                    for dx in -(radius as isize)..=radius as isize {
+                        window.push(at(y + dy, x_idx + dx));
+                    }
+                }
+                window.sort();
+                assert_eq!(window[window.len() / 2], x.get_buffer()[y as usize * width + x_idx as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_operators_agree_on_step() {
+        let pixel_map = (0..10*10*3).map(|i| if (i / 3) % 10 < 5 { 0 } else { 120 }).collect::<Vec<u8>>();
+        let x = AnymapImage::ppm(pixel_map, 255, 10, 10).unwrap();
+        for operator in [GradientOperator::Sobel, GradientOperator::Prewitt, GradientOperator::Scharr] {
+            let (magnitude, direction) = x.gradient(operator).unwrap();
+            assert_eq!(magnitude.dimensions(), (10, 10));
+            assert_eq!(magnitude.get_buffer()[54], 120);
+            assert_eq!(magnitude.get_buffer()[52], 0);
+            assert_eq!(direction.get_buffer()[54], 0);
+        }
+    }
+
+    #[test]
+    fn canny_outlines_square() {
+        let pixel_map = (0..20*20).map(|i| if (5..15).contains(&(i % 20)) && (5..15).contains(&(i / 20)) { 255 } else { 0 }).collect::<Vec<u8>>();
+        let x = AnymapImage::pgm(pixel_map, 255, 20, 20).unwrap();
+        let edges = x.canny(1.0, 30.0, 80.0).unwrap();
+        let buffer = edges.get_buffer();
+        assert!(buffer.iter().all(|&b| b <= 1));
+        assert_eq!(buffer[10*20 + 10], 0);
+        assert_eq!(buffer[0], 0);
+        assert!((0..20).any(|x| buffer[4*20 + x] == 1 || buffer[5*20 + x] == 1));
+        assert!(AnymapImage::pbm(vec![0; 4], 2, 2).unwrap().canny(1.0, 1.0, 2.0).is_err());
+    }
+
+    #[test]
+    fn morphology_open_close_duality() {
+        let pixel_map = (0..16*16).map(|i| (((i * 7919) % 13) < 6) as u8).collect::<Vec<u8>>();
+        let element = StructuringElement::disk(1);
+
+        let mut opened = AnymapImage::pbm(pixel_map.clone(), 16, 16).unwrap();
+        opened.open(&element).unwrap();
+        let mut inverted_closed = AnymapImage::pbm(pixel_map.clone(), 16, 16).unwrap();
+        inverted_closed.invert();
+        inverted_closed.close(&element).unwrap();
+        inverted_closed.invert();
+
+        // opening is anti-extensive and matches the complement of closing the complement away from the border
+        assert!(opened.get_buffer().iter().zip(pixel_map.iter()).all(|(o, p)| o <= p));
+        for y in 2..14 {
+            for x in 2..14 {
+                assert_eq!(opened.get_buffer()[y*16 + x], inverted_closed.get_buffer()[y*16 + x]);
+            }
+        }
+
+        let mut ppm = AnymapImage::ppm(vec![0; 3*4], 255, 2, 2).unwrap();
+        assert!(ppm.erode(&element).is_err());
+        assert!(StructuringElement::new(vec![0, 0], 1, 2, (0, 0)).is_err());
+
+        // elements leaving their origin unset can erode above the dilation, differences clamp at 0
+        let offset = StructuringElement::new(vec![1, 0], 1, 2, (0, 1)).unwrap();
+        let mut gradient = AnymapImage::pgm(vec![50, 0, 10], 255, 1, 3).unwrap();
+        gradient.morphological_gradient(&offset).unwrap();
+        assert_eq!(vec![0, 0, 10], gradient.get_buffer());
+        let mut top_hat = AnymapImage::pgm(vec![50, 0, 10], 255, 1, 3).unwrap();
+        top_hat.top_hat(&offset).unwrap();
+        assert_eq!(vec![0, 0, 10], top_hat.get_buffer());
+        let mut black_hat = AnymapImage::pgm(vec![50, 0, 10], 255, 1, 3).unwrap();
+        black_hat.black_hat(&offset).unwrap();
+        assert_eq!(vec![0, 0, 0], black_hat.get_buffer());
+    }
+
+    #[test]
+    fn skeletonize_keeps_lines() {
+        let mut pixel_map = vec![0; 9*9];
+        for x in 1..8 { pixel_map[4*9 + x] = 1; }
+        let mut x = AnymapImage::pbm(pixel_map.clone(), 9, 9).unwrap();
+        x.skeletonize().unwrap();
+        assert_eq!(pixel_map, x.get_buffer());
+    }
+
+    #[test]
+    fn threshold_methods_split_bimodal_image() {
+        let pixel_map = (0..20*20*3).map(|i| if (i / 3) % 20 < 8 { 30 + (i % 7) as u8 } else { 200 + (i % 5) as u8 }).collect::<Vec<u8>>();
+        let x = AnymapImage::ppm(pixel_map, 255, 20, 20).unwrap();
+        let expected = (0..20*20).map(|i| (i % 20 < 8) as u8).collect::<Vec<u8>>();
+
+        assert_eq!(expected, x.threshold(120).unwrap().get_buffer());
+        assert_eq!(expected, x.threshold_otsu().unwrap().get_buffer());
+        assert!(x.threshold_adaptive(AdaptiveMethod::Gaussian, 4, 0.0).is_err());
+
+        // the fixed threshold is inclusive
+        let levels = AnymapImage::pgm(vec![0, 119, 120, 121, 255], 255, 1, 5).unwrap();
+        assert_eq!(vec![1, 1, 1, 0, 0], levels.threshold(120).unwrap().get_buffer());
+
+        // otsu splits unbalanced classes at the top of the dark one
+        let unbalanced = AnymapImage::pgm(vec![10, 12, 14, 180, 182, 184, 186, 188, 190, 192], 255, 2, 5).unwrap();
+        assert_eq!(vec![1, 1, 1, 0, 0, 0, 0, 0, 0, 0], unbalanced.threshold_otsu().unwrap().get_buffer());
+
+        // a dark tail next to one dominant bright peak falls below the triangle threshold
+        let mut pixel_map = vec![230; 10*10];
+        pixel_map[..10].copy_from_slice(&[5, 20, 40, 60, 80, 100, 120, 140, 160, 180]);
+        let peaked = AnymapImage::pgm(pixel_map, 255, 10, 10).unwrap();
+        let expected = (0..10*10).map(|i| (i < 10) as u8).collect::<Vec<u8>>();
+        assert_eq!(expected, peaked.threshold_triangle().unwrap().get_buffer());
+
+        // a dark spot stands out with a positive offset, a bright one is the only pixel left white with a negative offset
+        let mut dark_spot = vec![100; 5*5];
+        dark_spot[12] = 40;
+        let dark_spot = AnymapImage::pgm(dark_spot, 255, 5, 5).unwrap();
+        let mut bright_spot = vec![100; 5*5];
+        bright_spot[12] = 200;
+        let bright_spot = AnymapImage::pgm(bright_spot, 255, 5, 5).unwrap();
+        let center = (0..5*5).map(|i| (i == 12) as u8).collect::<Vec<u8>>();
+        let around = (0..5*5).map(|i| (i != 12) as u8).collect::<Vec<u8>>();
+        for method in [AdaptiveMethod::Mean, AdaptiveMethod::Gaussian] {
+            assert_eq!(center, dark_spot.threshold_adaptive(method, 3, 10.0).unwrap().get_buffer());
+            assert_eq!(around, bright_spot.threshold_adaptive(method, 3, -10.0).unwrap().get_buffer());
+        }
+
+        let flat = AnymapImage::pgm(vec![90; 6*6], 255, 6, 6).unwrap();
+        for method in [AdaptiveMethod::Mean, AdaptiveMethod::Gaussian] {
+            assert_eq!(vec![0; 6*6], flat.threshold_adaptive(method, 5, 2.0).unwrap().get_buffer());
+            assert_eq!(vec![1; 6*6], flat.threshold_adaptive(method, 5, -2.0).unwrap().get_buffer());
+        }
+    }
+
+    #[test]
+    fn dithering_preserves_average_tone() {
+        let pixel_map = (0..32*32).map(|i| ((i % 32) * 8) as u8).collect::<Vec<u8>>();
+        let x = AnymapImage::pgm(pixel_map.clone(), 255, 32, 32).unwrap();
+        let mean = pixel_map.iter().map(|&b| b as f64).sum::<f64>() / pixel_map.len() as f64;
+
+        for method in [DitherMethod::FloydSteinberg, DitherMethod::Atkinson, DitherMethod::JarvisJudiceNinke, DitherMethod::Sierra, DitherMethod::Bayer(8)] {
+            let pbm = x.dither_to_pbm(method).unwrap();
+            let white = pbm.get_buffer().iter().filter(|&&b| b == 0).count() as f64 / (32.0 * 32.0);
+            assert!((white * 255.0 - mean).abs() < 12.0, "{:?} drifted: {}", method, white * 255.0);
+
+            let mut reduced = x.clone();
+            reduced.reduce_saturation(3, method).unwrap();
+            assert!(reduced.get_buffer().iter().all(|&b| b <= 3));
+        }
+        assert!(x.dither_to_pbm(DitherMethod::Bayer(3)).is_err());
+        assert!(x.dither_to_pbm(DitherMethod::Bayer(64)).is_ok());
+        assert!(x.dither_to_pbm(DitherMethod::Bayer(128)).is_err());
+        assert!(x.dither_to_pbm(DitherMethod::Bayer(1 << 40)).is_err());
+        assert!(x.clone().reduce_saturation(300, DitherMethod::Nearest).is_err());
+    }
+
+    #[test]
+    fn dither_to_palette_only_uses_palette() {
+        let pixel_map = (0..16*16*3).map(|i| ((i * 31) % 256) as u8).collect::<Vec<u8>>();
+        let mut x = AnymapImage::ppm(pixel_map, 255, 16, 16).unwrap();
+        let palette = [[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 0, 255]];
+        x.dither_to_palette(&palette, DitherMethod::FloydSteinberg).unwrap();
+        assert!(x.get_buffer().chunks(3).all(|p| palette.iter().any(|c| c[..] == *p)));
+        assert!(AnymapImage::pgm(vec![0; 4], 255, 2, 2).unwrap().dither_to_palette(&palette, DitherMethod::Nearest).is_err());
+    }
+
+    #[test]
+    fn histogram_counts_every_pixel() {
+        let pixel_map = (0..10*10*3).map(|i| (i % 256) as u8).collect::<Vec<u8>>();
+        let x = AnymapImage::ppm(pixel_map, 255, 10, 10).unwrap();
+        let histograms = x.histogram();
+        assert_eq!(3, histograms.len());
+        assert!(histograms.iter().all(|h| h.len() == 256 && h.iter().sum::<usize>() == 100));
+        assert!(x.cumulative_histogram().iter().all(|h| h[255] == 100));
+
+        let pbm = AnymapImage::pbm(vec![1, 0, 1, 1], 2, 2).unwrap();
+        assert_eq!(vec![vec![1, 3]], pbm.histogram());
+    }
+
+    #[test]
+    fn clahe_flattens_tile_histograms() {
+        let pixel_map = (0..32*32).map(|i| (60 + (i * 13) % 40) as u8).collect::<Vec<u8>>();
+        let mut equalized = AnymapImage::pgm(pixel_map.clone(), 255, 32, 32).unwrap();
+        equalized.equalize_histogram().unwrap();
+        let buffer = equalized.get_buffer();
+        assert_eq!(0, *buffer.iter().min().unwrap());
+        assert_eq!(255, *buffer.iter().max().unwrap());
+
+        let mut clahe = AnymapImage::pgm(pixel_map, 255, 32, 32).unwrap();
+        clahe.clahe((4, 4), 2.0).unwrap();
+        let buffer = clahe.get_buffer();
+        assert!(*buffer.iter().max().unwrap() - *buffer.iter().min().unwrap() > 40);
+        assert!(clahe.clahe((0, 4), 2.0).is_err());
+        assert!(clahe.clahe((4, 4), 0.5).is_err());
+    }
+
+    #[test]
+    fn tone_adjustments_respect_saturation() {
+        let pixel_map = (0..=15).collect::<Vec<u8>>();
+        let mut x = AnymapImage::pgm(pixel_map.clone(), 15, 4, 4).unwrap();
+        x.brightness_contrast(40.0, 3.0).unwrap();
+        assert!(x.get_buffer().iter().all(|&b| b <= 15));
+
+        let mut x = AnymapImage::pgm(pixel_map.clone(), 15, 4, 4).unwrap();
+        x.levels(0, 15, 1.0, 15, 0).unwrap();
+        assert_eq!(pixel_map.iter().rev().copied().collect::<Vec<u8>>(), x.get_buffer());
+
+        let mut x = AnymapImage::ppm(vec![10, 20, 30], 255, 1, 1).unwrap();
+        x.adjust_gamma(1.0).unwrap();
+        x.curves(&[(0, 0), (255, 255)]).unwrap();
+        assert_eq!(vec![10, 20, 30], x.get_buffer());
+        assert!(x.curves(&[(5, 0), (5, 10)]).is_err());
+        assert!(x.levels(100, 50, 1.0, 0, 255).is_err());
+        assert!(x.apply_lookup_table(&[0; 10]).is_err());
+        assert!(AnymapImage::pbm(vec![0], 1, 1).unwrap().adjust_gamma(2.0).is_err());
+    }
+
+    #[test]
+    fn color_spaces_round_trip() {
+        let pixel_map = vec![255, 0, 0, 12, 200, 99, 128, 128, 128, 0, 0, 0, 255, 255, 255, 37, 61, 250];
+        let x = AnymapImage::ppm(pixel_map.clone(), 255, 2, 3).unwrap();
+        for space in [ColorSpace::Rgb, ColorSpace::LinearRgb, ColorSpace::Hsv, ColorSpace::Hsl,
+                      ColorSpace::YCbCr(YCbCrStandard::Bt601), ColorSpace::YCbCr(YCbCrStandard::Bt709),
+                      ColorSpace::Xyz, ColorSpace::Lab] {
+            let pixels = x.to_color_space(space).unwrap();
+            let y = AnymapImage::from_color_space(&pixels, space, 255, 2, 3).unwrap();
+            assert_eq!(pixel_map, y.get_buffer(), "{:?} did not round trip", space);
+        }
+
+        let lab = x.to_color_space(ColorSpace::Lab).unwrap();
+        assert!((lab[4][0] - 100.0).abs() < 0.01 && lab[4][1].abs() < 0.01 && lab[4][2].abs() < 0.01);
+
+        let mut y = x.clone();
+        y.adjust_hue(360.0).unwrap();
+        y.adjust_saturation(1.0).unwrap();
+        assert_eq!(pixel_map, y.get_buffer());
+        assert!(AnymapImage::pgm(vec![0], 255, 1, 1).unwrap().adjust_hue(10.0).is_err());
+    }
+
+    #[test]
+    fn greyscale_covers_every_pixel() {
+        let pixel_map = vec![30, 60, 90, 255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let mut x = AnymapImage::ppm(pixel_map.clone(), 255, 2, 2).unwrap();
+        x.greyscale();
+        assert_eq!(vec![60, 60, 60, 85, 85, 85, 85, 85, 85, 85, 85, 85], x.get_buffer());
+
+        let x = AnymapImage::ppm(pixel_map, 255, 2, 2).unwrap();
+        assert_eq!(vec![54, 76, 150, 29], x.to_pgm(GreyscaleMethod::Rec601).unwrap().get_buffer());
+        assert_eq!(vec![90, 0, 0, 255], x.to_pgm(GreyscaleMethod::Channel(2)).unwrap().get_buffer());
+        assert_eq!(vec![60, 128, 128, 128], x.to_pgm(GreyscaleMethod::Desaturate).unwrap().get_buffer());
+        for method in [GreyscaleMethod::Rec709, GreyscaleMethod::LinearLuminance, GreyscaleMethod::Lightness] {
+            let pgm = x.to_pgm(method).unwrap();
+            assert_eq!((2, 2), pgm.dimensions());
+            assert!(pgm.get_buffer()[2] > pgm.get_buffer()[3]);
+        }
+        assert!(x.to_pgm(GreyscaleMethod::Channel(3)).is_err());
+        assert_eq!(vec![0, 1], AnymapImage::pbm(vec![1, 0], 1, 2).unwrap().to_pgm(GreyscaleMethod::Average).unwrap().get_buffer());
+    }
+
+    #[test]
+    fn split_merge_swizzle_channels() {
+        let pixel_map = (0..4*5*3).map(|i| (i * 4) as u8).collect::<Vec<u8>>();
+        let x = AnymapImage::ppm(pixel_map.clone(), 255, 4, 5).unwrap();
+        let [red, green, blue] = x.split_channels().unwrap();
+        assert_eq!((4, 5), green.dimensions());
+        assert_eq!(pixel_map, AnymapImage::merge_channels(&red, &green, &blue).unwrap().get_buffer());
+
+        let mut swizzled = x.clone();
+        swizzled.swizzle("BGR").unwrap();
+        assert_eq!(AnymapImage::merge_channels(&blue, &green, &red).unwrap().get_buffer(), swizzled.get_buffer());
+        assert!(swizzled.swizzle("rgba").is_err());
+        assert!(swizzled.swizzle("rgx").is_err());
+
+        let small = AnymapImage::pgm(vec![0; 4], 255, 2, 2).unwrap();
+        assert!(AnymapImage::merge_channels(&red, &green, &small).is_err());
+        assert!(small.split_channels().is_err());
+    }
+
+    #[test]
+    fn compare_metrics_rank_distortions() {
+        let pixel_map = (0..40*40*3).map(|i| ((i / 3 % 40) * 6 + (i / 120) * 2) as u8).collect::<Vec<u8>>();
+        let golden = AnymapImage::ppm(pixel_map.clone(), 255, 40, 40).unwrap();
+        let slightly = AnymapImage::ppm(pixel_map.iter().map(|&b| b.saturating_add(2)).collect(), 255, 40, 40).unwrap();
+        let mut blurred = golden.clone();
+        blurred.box_blur(3).unwrap();
+
+        let small = compare::summary(&golden, &slightly).unwrap();
+        let large = compare::summary(&golden, &blurred).unwrap();
+        assert!(small.mse.combined < large.mse.combined);
+        assert!(small.psnr.combined > large.psnr.combined);
+        assert!(small.ssim.combined > large.ssim.combined);
+        assert!(small.ms_ssim.combined > large.ms_ssim.combined);
+        assert!(small.max_error.combined <= 2.0);
+        assert_eq!(3, large.rmse.channels.len());
+
+        let pgm = AnymapImage::pgm(vec![0; 40*40], 255, 40, 40).unwrap();
+        assert!(compare::mse(&golden, &pgm).is_err());
+        assert!(compare::ssim(&pgm, &AnymapImage::pgm(vec![0; 4], 255, 2, 2).unwrap()).is_err());
+    }
+
+    #[test]
+    fn diff_image_locates_changes() {
+        let golden = AnymapImage::ppm(vec![40; 10*10*3], 255, 10, 10).unwrap();
+        let mut pixel_map = golden.get_buffer();
+        for (y, x) in [(2, 3), (6, 7), (4, 5)] {
+            pixel_map[(y*10 + x)*3 + 1] = 240;
+        }
+        let changed = AnymapImage::ppm(pixel_map, 255, 10, 10).unwrap();
+
+        let heatmap = compare::diff_image(&golden, &changed, &DiffOptions::default()).unwrap();
+        assert_eq!(3, heatmap.differing_pixels);
+        assert_eq!(Some(((2, 3), (6, 7))), heatmap.bounding_box);
+        assert_eq!((10, 10), heatmap.image.dimensions());
+        assert_eq!(vec![0, 0, 0], heatmap.image.get_buffer()[..3].to_vec());
+        assert!(heatmap.image.get_buffer()[(2*10 + 3)*3] > 200);
+
+        let tolerant = compare::diff_image(&golden, &changed, &DiffOptions { style: DiffStyle::Overlay, tolerance: 200 }).unwrap();
+        assert_eq!(0, tolerant.differing_pixels);
+        assert_eq!(None, tolerant.bounding_box);
+        assert!(tolerant.image.get_buffer().iter().all(|&b| b == 40));
+    }
+
+    #[test]
+    fn snapshots_catch_regressions() {
+        let buffer = (0..16*16).map(|i| ((i % 16) * 16) as u8).collect::<Vec<u8>>();
+        let mut gradient = AnymapImage::pgm(buffer, 255, 16, 16).unwrap();
+        gradient.box_blur(1).unwrap();
+        assert_image_matches_snapshot!(gradient, "box_blurred_gradient");
+        assert_image_eq!(gradient, gradient.clone());
+
+        // the failure paths below cannot be exercised while blessing
+        if std::env::var(testing::BLESS_VAR).is_ok() { return; }
+
+        let directory = std::env::temp_dir().join("ductr_snapshot_test");
+        let directory = directory.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(directory);
+        let error = testing::check_snapshot(&gradient, directory, "gradient", 0).unwrap_err();
+        assert!(error.contains("does not exist"));
+        std::fs::rename(format!("{}/gradient.new.pgm", directory), format!("{}/gradient.pgm", directory)).unwrap();
+        assert!(testing::check_snapshot(&gradient, directory, "gradient", 0).is_ok());
+
+        gradient.invert();
+        assert!(testing::check_snapshot(&gradient, directory, "gradient", 0).is_err());
+        assert!(AnymapImage::read_from_binary(&format!("{}/gradient.diff.ppm", directory)).is_ok());
+        assert!(testing::check_image_eq(&gradient, &AnymapImage::pgm(vec![0; 4], 255, 2, 2).unwrap(), 0).is_err());
+
+        // an explicit directory wins over the environment variable, which only replaces the default
+        std::env::set_var(testing::SNAPSHOT_DIR_VAR, std::env::temp_dir().join("ductr_snapshot_elsewhere"));
+        assert!(testing::check_snapshot(&gradient, directory, "gradient", 0).unwrap_err().contains(directory));
+        assert_ne!(directory, testing::snapshot_dir(directory));
+        std::env::remove_var(testing::SNAPSHOT_DIR_VAR);
+        assert_eq!(directory, testing::snapshot_dir(directory));
+    }
+
+    #[test]
+    fn debug_dump_infers_format() {
+        let directory = std::env::temp_dir().join("ductr_dump_test");
+        std::env::set_var("DUCTR_DUMP_DIR", &directory);
+
+        let pbm = debug_dump_as(&[0, 1, 1, 0], 2, 2, "pbm").unwrap();
+        let pgm = debug_dump(&[0, 64, 128, 255], 2, 2).unwrap();
+        let ppm = debug_dump(&[255, 0, 0].repeat(4), 2, 2).unwrap();
+        assert!(pbm.ends_with(".pbm") && pgm.ends_with(".pgm") && ppm.ends_with(".ppm"));
+        assert_ne!(pbm[..pbm.len() - 4], pgm[..pgm.len() - 4]);
+        assert_eq!(vec![0, 64, 128, 255], AnymapImage::read_from_binary(&pgm).unwrap().get_buffer());
+        assert!(debug_dump(&[0; 5], 2, 2).is_err());
+        assert!(debug_dump_as(&[0, 2, 0, 0], 2, 2, "pbm").is_err());
+        assert!(debug_dump_as(&[0; 4], 2, 2, "png").is_err());
+
+        // single channel buffers of zeros and ones stay greyscale, so black stays black
+        let black = debug_dump(&[0; 3*2], 3, 2).unwrap();
+        assert!(black.ends_with(".pgm"));
+        let black = AnymapImage::read_from_binary(&black).unwrap().to_format("ppm").unwrap();
+        assert_eq!(vec![0; 3*2*3], black.get_buffer());
+        let mask = debug_dump(&[0, 1, 1, 0], 2, 2).unwrap();
+        assert_eq!(vec![0, 1, 1, 0], AnymapImage::read_from_binary(&mask).unwrap().get_buffer());
+
+        let gradient = (0..6).map(|i| i * 40).collect::<Vec<u8>>();
+        dump!(gradient, 3, 2);
+        dump!(gradient.iter().map(|&v| (v < 100) as u8).collect::<Vec<u8>>(), 3, 2, "pbm");
+
+        // without the feature the arguments are not even evaluated
+        #[cfg(not(feature = "dump"))]
+        {
+            let mut evaluated = false;
+            dump!({ evaluated = true; gradient.clone() }, 3, 2);
+            assert!(!evaluated);
+        }
+    }
+
+    #[test]
+    fn scalar_fields_follow_colormaps() {
+        // entries 0, 64, 128, 191 and 255 of the matplotlib and Google tables, the stops of CoolWarm and the corners of Jet
+        let table_points = [0.0, 64.0 / 255.0, 128.0 / 255.0, 191.0 / 255.0, 1.0];
+        let references = [(ColorMap::Viridis, table_points, [[68, 1, 84], [59, 82, 139], [33, 145, 140], [92, 200, 99], [253, 231, 37]]),
+                          (ColorMap::Magma, table_points, [[0, 0, 4], [81, 18, 124], [183, 55, 121], [251, 135, 97], [252, 253, 191]]),
+                          (ColorMap::Inferno, table_points, [[0, 0, 4], [87, 16, 110], [188, 55, 84], [249, 140, 10], [252, 255, 164]]),
+                          (ColorMap::Plasma, table_points, [[13, 8, 135], [126, 3, 168], [204, 71, 120], [248, 148, 65], [240, 249, 33]]),
+                          (ColorMap::Turbo, table_points, [[48, 18, 59], [40, 188, 235], [164, 252, 60], [251, 129, 34], [122, 4, 3]]),
+                          (ColorMap::CoolWarm, [0.0, 0.25, 0.5, 0.75, 1.0], [[59, 76, 192], [141, 176, 254], [221, 221, 221], [244, 154, 123], [180, 4, 38]]),
+                          (ColorMap::Jet, [0.0, 0.125, 0.375, 0.625, 1.0], [[0, 0, 128], [0, 0, 255], [0, 255, 255], [255, 255, 0], [128, 0, 0]]),
+                          (ColorMap::Grey, [0.0, 0.25, 0.5, 0.75, 1.0], [[0, 0, 0], [64, 64, 64], [128, 128, 128], [191, 191, 191], [255, 255, 255]])];
+        for (colormap, points, colors) in references {
+            for (t, expected) in points.into_iter().zip(colors) {
+                let color = colormap.sample(t);
+                assert!(color.iter().zip(expected).all(|(&a, b)| (a as i32 - b).abs() <= 1), "{:?} at {}: {:?}", colormap, t, color);
+            }
+        }
+
+        let sdf: Vec<i32> = vec![-4, -2, 0, 2, 4, 8];
+        let field = AnymapImage::from_scalar_field(&sdf, 3, 2, ColorMap::CoolWarm, ScalarRange::Fixed(-4.0, 4.0)).unwrap();
+        assert_eq!((2, 3), field.dimensions());
+        assert_eq!(ColorMap::CoolWarm.sample(0.5).to_vec(), field.get_buffer()[6..9].to_vec());
+        assert_eq!(field.get_buffer()[12..15], field.get_buffer()[15..18]);
+
+        let losses = vec![f64::NAN, 1.0, f64::INFINITY, 3.0];
+        let auto = AnymapImage::from_scalar_field(&losses, 2, 2, ColorMap::Turbo, ScalarRange::Auto).unwrap();
+        assert_eq!(vec![0, 0, 0], auto.get_buffer()[..3].to_vec());
+        assert_eq!(auto.get_buffer()[6..9], auto.get_buffer()[9..12]);
+
+        assert!(AnymapImage::from_scalar_field(&sdf, 2, 2, ColorMap::Jet, ScalarRange::Auto).is_err());
+        assert!(AnymapImage::from_scalar_field(&sdf, 3, 2, ColorMap::Jet, ScalarRange::Fixed(1.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn draw_shapes_clip_and_match_formats() {
+        let mut ppm = AnymapImage::ppm(vec![0; 20*20*3], 255, 20, 20).unwrap();
+        ppm.fill_rectangle((-5, -5), (4, 4), Color::Rgb([255, 0, 0])).unwrap();
+        ppm.draw_circle((19, 19), 6, Color::Rgb([0, 255, 0]), 3).unwrap();
+        ppm.draw_line_aa((0, 19), (19, 10), Color::Rgb([0, 0, 255])).unwrap();
+        let buffer = ppm.get_buffer();
+        assert_eq!(25, buffer.chunks(3).filter(|p| *p == [255, 0, 0]).count());
+        assert!(buffer.chunks(3).filter(|p| *p == [0, 255, 0]).count() > 20);
+        assert!(buffer.chunks(3).any(|p| p[2] > 0 && p[2] < 255));
+
+        // a thick line covers a band as wide as its thickness
+        let mut pgm = AnymapImage::pgm(vec![0; 9*9], 255, 9, 9).unwrap();
+        pgm.draw_line((0, 4), (8, 4), Color::Grey(200), 3).unwrap();
+        assert_eq!(27, pgm.get_buffer().iter().filter(|&&v| v == 200).count());
+
+        // a filled polygon covers its outline
+        let triangle = [(1, 1), (7, 1), (4, 7)];
+        let mut filled = AnymapImage::pbm(vec![0; 9*9], 9, 9).unwrap();
+        let mut outlined = filled.clone();
+        filled.fill_polygon(&triangle, Color::Bit(1)).unwrap();
+        outlined.draw_polygon(&triangle, Color::Bit(1), 1).unwrap();
+        assert!(filled.get_buffer().iter().zip(outlined.get_buffer()).all(|(&f, o)| f >= o));
+        assert!(filled.get_buffer().iter().filter(|&&b| b == 1).count() > outlined.get_buffer().iter().filter(|&&b| b == 1).count());
+
+        assert!(pgm.draw_line((0, 0), (1, 1), Color::Rgb([0, 0, 0]), 1).is_err());
+        assert!(filled.draw_line((0, 0), (1, 1), Color::Bit(2), 1).is_err());
+        assert!(pgm.draw_crosshair((4, 4), 2, Color::Grey(1), 0).is_err());
+
+        // shapes reaching far outside the canvas are clipped before they are walked
+        let far = 1_000_000_000_000;
+        let mut clipped = AnymapImage::pbm(vec![0; 5*5], 5, 5).unwrap();
+        clipped.draw_line((-far, -far), (far, far), Color::Bit(1), 1).unwrap();
+        assert_eq!((0..5*5).map(|i| (i % 6 == 0) as u8).collect::<Vec<u8>>(), clipped.get_buffer());
+        clipped.draw_line((-far, 2), (far, 2), Color::Bit(1), 3).unwrap();
+        assert_eq!(vec![1; 5*3], clipped.get_buffer()[5..5*4].to_vec());
+        clipped.draw_line((-far, -far), (-far, far), Color::Bit(0), 1).unwrap();
+        clipped.fill_rectangle((-far, -far), (far, far), Color::Bit(0)).unwrap();
+        assert_eq!(vec![0; 5*5], clipped.get_buffer());
+
+        let mut blended = AnymapImage::pgm(vec![0; 4*4], 255, 4, 4).unwrap();
+        blended.draw_line_aa((-far, 1), (far, 1), Color::Grey(255)).unwrap();
+        blended.draw_line_aa((2, far), (2, -far), Color::Grey(255)).unwrap();
+        assert_eq!(vec![0, 0, 255, 0,
+                        255, 255, 255, 255,
+                        0, 0, 255, 0,
+                        0, 0, 255, 0], blended.get_buffer());
+    }
+
+    #[test]
+    fn text_stays_inside_measured_box() {
+        for font in [Font::Small, Font::Large] {
+            let style = TextStyle { font, scale: 2, background: Some(Color::Grey(50)), padding: 3, line_spacing: 1 };
+            let text = "frame 42\nloss: 0.25";
+            let (width, height) = draw::measure_text(text, &style);
+
+            let mut pgm = AnymapImage::pgm(vec![0; 80*200], 255, 80, 200).unwrap();
+            pgm.draw_text_with(10, 10, text, Color::Grey(255), &style).unwrap();
+            let buffer = pgm.get_buffer();
+            let lit = (0..buffer.len()).filter(|&i| buffer[i] == 255).collect::<Vec<usize>>();
+            assert!(!lit.is_empty());
+            assert!(lit.iter().all(|&i| (10..10 + width).contains(&(i % 200)) && (10..10 + height).contains(&(i / 200))));
+            assert_eq!((width + 6) * (height + 6), buffer.iter().filter(|&&v| v != 0).count());
+        }
+
+        let mut pbm = AnymapImage::pbm(vec![0; 8*8], 8, 8).unwrap();
+        pbm.draw_text(-2, -3, "\u{e9}", Color::Bit(1), 1).unwrap();
+        assert!(pbm.draw_text(0, 0, "a", Color::Bit(1), 0).is_err());
+        assert_eq!((0, 0), draw::measure_text("", &TextStyle::default()));
+    }
+
+    #[test]
+    fn plots_draw_series_in_their_colors() {
+        let options = plot::PlotOptions { title: "metrics".to_string(), x_label: "epoch".to_string(), y_label: "loss".to_string(), ..plot::PlotOptions::default() };
+        let train = plot::Series::from_values("train", &[1.0, 0.6, 0.4, 0.3]).with_color([255, 0, 0]);
+        let test = plot::Series::from_values("test", &[1.1, 0.8, 0.7, 0.65]).with_color([0, 0, 255]);
+
+        let count = |image: &AnymapImage, color: [u8; 3]| image.get_buffer().chunks(3).filter(|p| *p == color).count();
+        for chart in [plot::line_chart(&[train.clone(), test.clone()], &options).unwrap(),
+                      plot::scatter_plot(&[train.clone(), test.clone()], &options).unwrap(),
+                      plot::bar_chart(&["a", "b", "c", "d"], &[train.clone(), test.clone()], &options).unwrap()] {
+            assert_eq!((320, 480), chart.dimensions());
+            assert!(count(&chart, [255, 0, 0]) > 10);
+            assert!(count(&chart, [0, 0, 255]) > 10);
+            assert!(count(&chart, [0, 0, 0]) > 100);
+        }
+
+        let flat = plot::histogram(&[2.0; 10], 4, &plot::PlotOptions::default()).unwrap();
+        assert!(flat.get_buffer().chunks(3).any(|p| p != [255, 255, 255]));
+        assert!(plot::line_chart(&[plot::Series::from_values("nan", &[f64::NAN])], &options).is_err());
+        assert!(plot::histogram(&[1.0], 0, &options).is_err());
+        assert!(plot::scatter_plot(&[train], &plot::PlotOptions { width: 50, ..plot::PlotOptions::default() }).is_err());
+    }
+
+    #[test]
+    fn montage_tiles_and_promotes_images() {
+        let red = AnymapImage::ppm([255, 0, 0].repeat(4*4), 255, 4, 4).unwrap();
+        let grey = AnymapImage::pgm(vec![7; 2*3], 7, 2, 3).unwrap();
+        let black = AnymapImage::pbm(vec![1; 3*2], 3, 2).unwrap();
+
+        let sheet = AnymapImage::montage(&[red.clone(), grey.clone(), black.clone()], 2, 1, [0, 0, 255], None).unwrap();
+        assert_eq!((1 + 4 + 1 + 4 + 1, 1 + 4 + 1 + 4 + 1), sheet.dimensions());
+        let pixel = |image: &AnymapImage, y: usize, x: usize| image.get_buffer()[(y*image.dimensions().1 + x)*3..(y*image.dimensions().1 + x)*3 + 3].to_vec();
+        assert_eq!(vec![0, 0, 255], pixel(&sheet, 0, 0));
+        assert_eq!(vec![255, 0, 0], pixel(&sheet, 1, 1));
+        assert_eq!(vec![255, 255, 255], pixel(&sheet, 1, 6));
+        assert_eq!(vec![0, 0, 0], pixel(&sheet, 6, 2));
+
+        let wide_red = AnymapImage::ppm([255, 0, 0].repeat(4*6), 255, 4, 6).unwrap();
+        let labelled = AnymapImage::montage(&[wide_red.clone(), wide_red], 1, 0, [255, 255, 255], Some(&["a", "b"])).unwrap();
+        assert_eq!((2*(4 + 10), 6), labelled.dimensions());
+        assert!(labelled.get_buffer().chunks(3).any(|p| p == [0, 0, 0]));
+
+        let wide = AnymapImage::hconcat(&[grey.clone(), grey.clone()], 2, [0, 0, 0]).unwrap();
+        assert_eq!((2, 8), wide.dimensions());
+        assert_eq!(7, wide.get_buffer()[5]);
+        let tall = AnymapImage::vconcat(&[black.clone(), black], 1, [255, 255, 255]).unwrap();
+        assert_eq!((7, 2), tall.dimensions());
+        assert_eq!(vec![1, 1, 0, 0, 1, 1], tall.get_buffer()[4..10].to_vec());
+
+        assert!(AnymapImage::montage(&[], 2, 0, [0, 0, 0], None).is_err());
+        assert!(AnymapImage::montage(std::slice::from_ref(&red), 0, 0, [0, 0, 0], None).is_err());
+        assert!(AnymapImage::montage(&[red], 1, 0, [0, 0, 0], Some(&[])).is_err());
+    }
+
+    #[test]
+    fn paste_clips_masks_and_blends() {
+        let mut canvas = AnymapImage::ppm(vec![0; 4*4*3], 255, 4, 4).unwrap();
+        let patch = AnymapImage::ppm([10, 20, 30].repeat(3*3), 255, 3, 3).unwrap();
+
+        // only the bottom right 2x2 corner of the patch lands on the canvas
+        canvas.paste(&patch, -1, -1).unwrap();
+        assert_eq!(4, canvas.get_buffer().chunks(3).filter(|p| *p == [10, 20, 30]).count());
+        canvas.paste(&patch, 3, 3).unwrap();
+        assert_eq!(vec![10, 20, 30], canvas.get_buffer()[45..48].to_vec());
+        canvas.paste(&patch, 10, -10).unwrap();
+
+        let ring = AnymapImage::pbm(vec![1, 1, 1, 1, 0, 1, 1, 1, 1], 3, 3).unwrap();
+        let mut masked = AnymapImage::pgm(vec![100; 3*3], 255, 3, 3).unwrap();
+        masked.paste_with(&AnymapImage::pgm(vec![50; 3*3], 255, 3, 3).unwrap(), 0, 0, Some(&ring), BlendMode::Add).unwrap();
+        assert_eq!(vec![150, 150, 150, 150, 100, 150, 150, 150, 150], masked.get_buffer());
+
+        let mut bits = AnymapImage::pbm(vec![0; 2*2], 2, 2).unwrap();
+        bits.paste(&AnymapImage::pgm(vec![0, 255, 255, 10], 255, 2, 2).unwrap(), 0, 0).unwrap();
+        assert_eq!(vec![1, 0, 0, 1], bits.get_buffer());
+
+        let grey = AnymapImage::pgm(vec![200; 1], 255, 1, 1).unwrap();
+        for (mode, expected) in [(BlendMode::Multiply, 78), (BlendMode::Screen, 222), (BlendMode::Difference, 100), (BlendMode::Darken, 100), (BlendMode::Lighten, 200)] {
+            let mut base = AnymapImage::pgm(vec![100; 1], 255, 1, 1).unwrap();
+            base.paste_with(&grey, 0, 0, None, mode).unwrap();
+            assert_eq!(vec![expected], base.get_buffer(), "{:?}", mode);
+        }
+
+        assert!(masked.paste_with(&patch, 0, 0, Some(&patch), BlendMode::Replace).is_err());
+        assert!(masked.paste_with(&patch, 0, 0, None, BlendMode::Alpha(1.5)).is_err());
+    }
+
+    #[test]
+    fn ansi_preview_fits_terminal() {
+        let buffer = (0..40*30).flat_map(|i| [(i % 40 * 6) as u8, (i / 40 * 8) as u8, 128]).collect::<Vec<u8>>();
+        let ppm = AnymapImage::ppm(buffer, 255, 30, 40).unwrap();
+
+        // 40x30 shrunk to 20x15, drawn as 8 rows of half blocks
+        let truecolor = ppm.to_ansi(20).unwrap();
+        assert_eq!(8, truecolor.lines().count());
+        assert!(truecolor.lines().all(|line| line.chars().filter(|&c| c == '\u{2580}').count() == 20 && line.ends_with("\x1b[0m")));
+        assert!(truecolor.contains("\x1b[49m"));
+
+        let palette = ppm.to_ansi_with(20, AnsiMode::Color256).unwrap();
+        assert!(palette.contains("\x1b[38;5;") && !palette.contains(";2;"));
+
+        let ascii = ppm.to_ansi_with(80, AnsiMode::Ascii).unwrap();
+        assert_eq!(15, ascii.lines().count());
+        assert!(ascii.lines().all(|line| line.len() == 40 && line.is_ascii()));
+
+        assert!(ppm.to_ansi(0).is_err());
+    }
+
+    #[test]
+    fn graphics_protocols_encode_images() {
+        let buffer = (0..64*40).flat_map(|i| [(i % 64 * 4) as u8, (i / 64 * 6) as u8, 90]).collect::<Vec<u8>>();
+        let ppm = AnymapImage::ppm(buffer, 255, 40, 64).unwrap();
+
+        // 40 rows make 7 sixel bands, the palette never exceeds the requested size
+        let sixel = ppm.to_sixel(16, DitherMethod::FloydSteinberg).unwrap();
+        assert!(sixel.starts_with("\x1bPq\"1;1;64;40") && sixel.ends_with("\x1b\\"));
+        assert_eq!(6, sixel.matches('-').count());
+        assert!(sixel.contains("#15;2;") && !sixel.contains("#16;2;"));
+
+        let mut written = Vec::new();
+        ppm.write_sixel(&mut written, 16, DitherMethod::FloydSteinberg).unwrap();
+        assert_eq!(sixel.as_bytes(), written.as_slice());
+
+        // 64*40*3 bytes make 10240 base64 characters, sent in three chunks
+        let kitty = ppm.to_kitty().unwrap();
+        assert_eq!(3, kitty.matches("\x1b_G").count());
+        assert!(kitty.starts_with("\x1b_Ga=T,f=24,s=64,v=40,m=1;") && kitty.contains("\x1b_Gm=0;"));
+
+        let iterm = ppm.to_iterm().unwrap();
+        assert!(iterm.starts_with("\x1b]1337;File=inline=1;") && iterm.contains("width=64px;height=40px"));
+
+        assert!(ppm.to_sixel(1, DitherMethod::Nearest).is_err());
+        assert!(ppm.to_sixel(257, DitherMethod::Nearest).is_err());
+    }
+
+    #[test]
+    fn stream_io_and_geometry() {
+        let pbm = AnymapImage::pbm((0..16*3).map(|i| (i % 3 == 0) as u8).collect(), 3, 16).unwrap();
+        let ppm = AnymapImage::ppm((0..4*6*3).map(|i| (i * 3) as u8).collect(), 255, 4, 6).unwrap();
+
+        // binary and ascii streams read back through the same entry point, rows of exactly 16 bits included
+        for image in [&pbm, &ppm] {
+            let (mut binary, mut ascii) = (Vec::new(), Vec::new());
+            image.write_binary_to(&mut binary).unwrap();
+            image.write_ascii_to(&mut ascii).unwrap();
+            assert_eq!(image.get_buffer(), AnymapImage::read_from_bytes(&binary).unwrap().get_buffer());
+            assert_eq!(image.get_buffer(), AnymapImage::read_from(&mut ascii.as_slice()).unwrap().get_buffer());
+        }
+        assert!(AnymapImage::read_from_bytes(b"P7").is_err());
+        // P4 rows are padded to a whole byte, widths that are a multiple of 8 carry no padding
+        let mut aligned = b"P4\n16 2\n".to_vec();
+        aligned.extend_from_slice(&[0b1111_0000, 0b0000_0001, 0b1000_0000, 0b1111_1111]);
+        let aligned = AnymapImage::read_from_bytes(&aligned).unwrap();
+        assert_eq!((2, 16), aligned.dimensions());
+        assert_eq!(vec![1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                        1, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1], aligned.get_buffer());
+        let mut padded = b"P4 5 3 ".to_vec();
+        padded.extend_from_slice(&[0b1010_1000, 0b0101_0111, 0b1100_0000]);
+        let padded = AnymapImage::read_from_bytes(&padded).unwrap();
+        assert_eq!(vec![1, 0, 1, 0, 1,
+                        0, 1, 0, 1, 0,
+                        1, 1, 0, 0, 0], padded.get_buffer());
+        let mut written = Vec::new();
+        padded.write_binary_to(&mut written).unwrap();
+        assert_eq!(padded.get_buffer(), AnymapImage::read_from_bytes(&written).unwrap().get_buffer());
+
+        for truncated in [&b"P5\n"[..], b"P5 2 2", b"P5 #", b"P4 8", b"P2\n2 2", b"P1", b"P3 1 1"] {
+            assert!(AnymapImage::read_from_bytes(truncated).unwrap_err().contains("incomplete header"));
+        }
+
+        let cropped = ppm.crop(1, 2, 2, 3).unwrap();
+        assert_eq!((2, 3), cropped.dimensions());
+        assert_eq!(&ppm.get_buffer()[24..27], &cropped.get_buffer()[..3]);
+        assert!(ppm.crop(3, 0, 2, 1).is_err());
+        assert!(ppm.crop(usize::MAX, 0, 1, 1).is_err());
+        assert!(ppm.crop(0, usize::MAX, 1, 1).is_err());
+        assert!(ppm.crop(1, 1, usize::MAX, usize::MAX).is_err());
+
+        assert_eq!((2, 3), ppm.resize(2, 3).unwrap().dimensions());
+        assert!(ppm.resize(usize::MAX, usize::MAX).is_err());
+        assert!(ppm.resize(1 << 31, 1 << 31).is_err());
+        assert_eq!(vec![0; 8], pbm.resize(1, 8).unwrap().get_buffer());
+        assert!(ppm.resize(0, 3).is_err());
+
+        assert_eq!((3, 16), pbm.to_format("ppm").unwrap().dimensions());
+        assert!(ppm.to_format("pam").is_err());
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn cli_pipes_through_stdio() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let run = |args: &[&str], input: &[u8]| {
+            let mut child = Command::new(env!("CARGO_BIN_EXE_ductr")).args(args)
+                                                                       .stdin(Stdio::piped())
+                                                                       .stdout(Stdio::piped())
+                                                                       .stderr(Stdio::piped())
+                                                                       .spawn()
+                                                                       .unwrap();
+            child.stdin.take().unwrap().write_all(input).unwrap();
+            child.wait_with_output().unwrap()
+        };
+
+        let output = run(&["convert", "-", "-", "--to", "pgm", "--ascii"], b"P3\n2 1\n255\n255 255 255 0 0 0");
+        assert!(output.status.success());
+        assert_eq!(b"P2\n2 1\n255\n255 0".to_vec(), output.stdout);
+
+        let output = run(&["invert", "-", "-"], b"P2\n2 1\n255\n10 200");
+        assert_eq!(b"P5\n2 1\n255\n\xf5\x37".to_vec(), output.stdout);
+
+        let info = run(&["info", "-"], b"P5\n2 1\n255\n\xf5\x37");
+        assert_eq!("format: pgm (binary)\ndimensions: 2x1\nsaturation: 255\n", String::from_utf8_lossy(&info.stdout));
+
+        let output = run(&["crop", "-", "-", "--top", "0"], b"P2\n2 1\n255\n10 200");
+        assert_eq!(Some(2), output.status.code());
+        let huge = usize::MAX.to_string();
+        let output = run(&["crop", "-", "-", "--top", &huge, "--left", "0", "--width", "1", "--height", "1"], b"P2\n2 1\n255\n10 200");
+        assert_eq!(Some(2), output.status.code());
+        let output = run(&["resize", "-", "-", "--width", &huge], b"P2\n2 1\n255\n10 200");
+        assert_eq!(Some(2), output.status.code());
+        assert!(output.stderr.starts_with(b"ductr: resized dimensions are too large"));
+        assert!(run(&["explode"], b"").stderr.starts_with(b"ductr: unknown command"));
+    }
+
+    #[test]
+    fn netpbm_filters_chain_like_a_pipeline() {
+        use ductr::netpbm;
+
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        let pipe = |program: netpbm::Program, list: &[&str], input: &[u8]| {
+            let mut output = Vec::new();
+            program(&args(list), &mut &input[..], &mut output).map(|_| output)
+        };
+
+        // pnmflip -cw | ppmtopgm | pamscale 2 | pnminvert
+        let ppm = b"P3\n2 1\n255\n255 0 0 0 0 255";
+        let rotated = pipe(netpbm::pnmflip, &["-cw"], ppm).unwrap();
+        let grey = pipe(netpbm::ppmtopgm, &[], &rotated).unwrap();
+        let scaled = pipe(netpbm::pamscale, &["2"], &grey).unwrap();
+        let inverted = pipe(netpbm::pnminvert, &["-plain"], &scaled).unwrap();
+        assert_eq!(b"P2\n2 4\n255\n179 179\n179 179\n226 226\n226 226".to_vec(), inverted);
+
+        // option values can be attached, and any unique prefix of a name works
+        let fitted = pipe(netpbm::pamscale, &["-xysize", "1", "1", "-pl"], &grey).unwrap();
+        assert_eq!(b"P2\n1 1\n255\n53".to_vec(), fitted);
+        let wide = pipe(netpbm::pamscale, &["--width=4"], &grey).unwrap();
+        assert_eq!((8, 4), AnymapImage::read_from_bytes(&wide).unwrap().dimensions());
+
+        let dithered = pipe(netpbm::pgmtopbm, &[], b"P2\n4 1\n255\n128 128 128 128").unwrap();
+        assert_eq!(2, AnymapImage::read_from_bytes(&dithered).unwrap().get_buffer().iter().filter(|&&b| b == 1).count());
+        // -value moves the Floyd-Steinberg threshold, lighter or darker, and is ignored by ordered dithering
+        let lighter = pipe(netpbm::pgmtopbm, &["-fs", "-value", "0.3", "-plain"], b"P2\n4 1\n255\n128 128 128 128").unwrap();
+        assert_eq!(b"P1\n4 1\n0 1 0 0".to_vec(), lighter);
+        let darker = pipe(netpbm::pgmtopbm, &["-value", "0.7", "-plain"], b"P2\n4 1\n255\n128 128 128 128").unwrap();
+        assert_eq!(b"P1\n4 1\n1 0 1 1".to_vec(), darker);
+        let ordered = pipe(netpbm::pgmtopbm, &["-d8", "-value", "0.9"], &grey).unwrap();
+        assert_eq!(ordered, pipe(netpbm::pgmtopbm, &["-d8"], &grey).unwrap());
+        assert!(pipe(netpbm::pgmtopbm, &["-fs", "-value", "1.5"], &grey).is_err());
+
+        let joined = pipe(netpbm::pnmcat, &["-lr", "-jbottom", "-black", "-", "-"], ppm);
+        assert!(joined.is_err());
+        let joined = pipe(netpbm::pnmcat, &["-tb", "-black", "-plain"], b"P1\n3 1\n0 1 0").unwrap();
+        assert_eq!(b"P1\n3 1\n0 1 0".to_vec(), joined);
+
+        assert!(pipe(netpbm::pnmflip, &["-r"], ppm).is_err());
+        assert!(pipe(netpbm::pamscale, &["1e300"], &grey).unwrap_err().contains("too large"));
+        assert!(pipe(netpbm::pamscale, &["-xsize", "4", "-yscale", "1e17"], &grey).is_err());
+        assert!(pipe(netpbm::pnmcat, &[], ppm).is_err());
+    }
+
+    #[test]
+    fn pipeline_validates_then_runs_over_files() {
+        let pipeline = Pipeline::new().greyscale(GreyscaleMethod::Rec709)
+                                      .crop(0, 0, 4, 4)
+                                      .gaussian_blur(0.8)
+                                      .then(Operation::Dither { method: DitherMethod::FloydSteinberg })
+                                      .then(Operation::RotateClockwise);
+        assert_eq!("pbm", pipeline.validate("ppm").unwrap());
+        assert_eq!(5, pipeline.steps().len());
+
+        // steps after a format change are checked against the new format
+        let blurred_mask = pipeline.clone().box_blur(1);
+        assert_eq!("Error: step 6 (box_blur): pbm images cannot be blurred", blurred_mask.validate("ppm").unwrap_err());
+        assert!(Pipeline::new().then(Operation::Dither { method: DitherMethod::Bayer(3) }).validate("pgm").is_err());
+        assert!(Pipeline::new().resize(0, 4).validate("pgm").is_err());
+        assert!(Pipeline::new().then(Operation::BrightnessContrast { brightness: f64::NAN, contrast: 1.0 }).validate("pgm").is_err());
+        assert!(Pipeline::new().then(Operation::BrightnessContrast { brightness: 10.0, contrast: f64::NAN }).validate("pgm").is_err());
+        assert!(Pipeline::new().then(Operation::BrightnessContrast { brightness: -10.0, contrast: 0.0 }).validate("pgm").is_ok());
+
+        // validation reports the same failure as running the step would
+        let mut pbm = AnymapImage::pbm(vec![0; 4], 2, 2).unwrap();
+        let runtime = pbm.adjust_gamma(2.0).unwrap_err();
+        assert_eq!(format!("Error: step 1 (adjust_gamma): {}", runtime.trim_start_matches("Error: ")),
+                   Pipeline::new().then(Operation::AdjustGamma { gamma: 2.0 }).validate("pbm").unwrap_err());
+
+        let dir = std::env::temp_dir().join("ductr_pipeline_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let colors = dir.join("colors.ppm");
+        let small = dir.join("small.pgm");
+        AnymapImage::ppm((0..6*8*3).map(|i| (i * 5) as u8).collect(), 255, 6, 8).unwrap().write_as_ascii(colors.to_str().unwrap()).unwrap();
+        AnymapImage::pgm(vec![100; 2*2], 255, 2, 2).unwrap().write_as_binary(small.to_str().unwrap()).unwrap();
+
+        let out = dir.join("out");
+        let results = pipeline.run_files(&[&colors, &small, &dir.join("missing.ppm")], out.to_str().unwrap());
+        let written = AnymapImage::read_from_binary(results[0].as_ref().unwrap()).unwrap();
+        assert!(results[0].as_ref().unwrap().ends_with("colors.pbm"));
+        assert_eq!((4, 4), written.dimensions());
+        assert!(results[1].as_ref().unwrap_err().contains("step 2 (crop)"));
+        assert!(results[2].is_err());
+
+        // offsets depend on the image, so huge ones pass validation and fail as an error of their step
+        let far_crop = Pipeline::new().invert().crop(usize::MAX, 0, 1, 1);
+        assert_eq!("pgm", far_crop.validate("pgm").unwrap());
+        let error = far_crop.apply(AnymapImage::pgm(vec![0; 4], 255, 2, 2).unwrap()).unwrap_err();
+        assert!(error.starts_with("Error: step 2 (crop): crop rectangle must fit inside the image"), "{}", error);
+    }
+
+    #[cfg(all(feature = "json", feature = "toml"))]
+    #[test]
+    fn pipeline_specs_deserialize() {
+        let toml = r#"
+            [[steps]]
+            op = "greyscale"
+
+            [[steps]]
+            op = "to_pgm"
+            method = { channel = 1 }
+
+            [[steps]]
+            op = "dither"
+            method = { bayer = 4 }
+        "#;
+        let expected = Pipeline::new().greyscale(GreyscaleMethod::Average)
+                                      .then(Operation::ToPgm { method: GreyscaleMethod::Channel(1) })
+                                      .then(Operation::Dither { method: DitherMethod::Bayer(4) });
+        assert_eq!(expected, Pipeline::from_toml(toml).unwrap());
+
+        let json = r#"{ "steps": [{ "op": "rotate180" }, { "op": "adjust_gamma", "gamma": 2.2 }, { "op": "convert", "format": "ppm" }] }"#;
+        let expected = Pipeline::new().then(Operation::Rotate180)
+                                      .then(Operation::AdjustGamma { gamma: 2.2 })
+                                      .convert("ppm");
+        assert_eq!(expected, Pipeline::from_json(json).unwrap());
+
+        assert!(Pipeline::from_json(r#"{ "steps": [{ "op": "sharpen" }] }"#).is_err());
+        assert!(Pipeline::from_json(r#"{ "steps": [{ "op": "box_blur", "radius": 1, "sigma": 2.0 }] }"#).is_err());
+    }
+
+    #[test]
+    fn invert_mirrors_values_within_saturation() {
+        let mut pgm = AnymapImage::pgm(vec![0, 30, 100], 100, 1, 3).unwrap();
+        pgm.invert();
+        assert_eq!(vec![100, 70, 0], pgm.get_buffer());
+
+        let mut ppm = AnymapImage::ppm(vec![0, 5, 15, 15, 10, 0], 15, 1, 2).unwrap();
+        ppm.invert();
+        assert_eq!(vec![15, 10, 0, 0, 5, 15], ppm.get_buffer());
+        ppm.invert();
+        assert_eq!(vec![0, 5, 15, 15, 10, 0], ppm.get_buffer());
+
+        let mut pbm = AnymapImage::pbm(vec![1, 0], 1, 2).unwrap();
+        pbm.invert();
+        assert_eq!(vec![0, 1], pbm.get_buffer());
+    }
+
+}
\ No newline at end of file